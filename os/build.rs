@@ -30,7 +30,8 @@ static TARGET_PATH: &str = "../user/target/riscv64gc-unknown-none-elf/release/";
 /// 生成的 link_app.S 文件包含以下内容：
 /// 1. _num_app 符号：存储用户程序的总数量
 /// 2. 应用程序地址表：每个应用的起始和结束地址标识符
-/// 3. 二进制数据：使用 .incbin 指令嵌入每个用户程序的二进制文件
+/// 3. _app_names 符号：按照与地址表相同的顺序排列的一组 NUL 结尾字符串，记录每个应用的名称
+/// 4. 二进制数据：使用 .incbin 指令嵌入每个用户程序的二进制文件
 ///
 /// 该文件会被内核链接时包含，使得内核能够在运行时访问和加载用户程序
 ///
@@ -80,6 +81,19 @@ _num_app:
     // 添加最后一个应用程序的结束地址，用于确定整个应用程序区域的边界
     writeln!(f, r#"    .quad app_{}_end"#, apps.len() - 1)?;
 
+    // 第三点五步：生成应用程序名称表
+    // 紧跟在地址表之后，按照与地址表相同的顺序（即排序后的顺序）
+    // 为每个应用程序生成一个以 NUL 结尾的字符串，供内核按名称定位应用程序的 ELF 数据
+    writeln!(
+        f,
+        r#"
+    .global _app_names
+_app_names:"#
+    )?;
+    for app in apps.iter() {
+        writeln!(f, r#"    .string "{}""#, app)?;
+    }
+
     // 第四步：为每个应用程序生成二进制数据段
     // 遍历所有应用程序，为每个应用生成对应的汇编代码段
     for (idx, app) in apps.iter().enumerate() {
@@ -1,6 +1,5 @@
 /// rCore 的配置文件，主要包括内核中的栈起始位置，栈大小，用户 App 数量和大小等
 
-pub const MAX_APP_NUM: usize = 16;
 pub const USER_STACK_SIZE: usize = 4096 * 2; // 8KB
 pub const KERNEL_STACK_SIZE: usize = 4096 * 2; // 8KB
 
@@ -13,12 +12,23 @@ pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
 
 pub use crate::board::{CLOCK_FREQ, MEMORY_END, MMIO};
 
+/// 时钟中断的触发频率，即每秒触发多少次时钟中断
+pub const TICKS_PER_SEC: usize = 100;
+
+/// stride 调度算法里的步长基数：每个任务每次被调度时，`stride` 累加
+/// `BIG_STRIDE / priority`。取一个足够大的值，使得任意两个任务的
+/// `stride` 之差远小于 `usize` 能表示的一半范围，从而 wrapping 比较始终正确
+pub const BIG_STRIDE: usize = 1 << 20;
+
+/// 任务创建时的默认优先级，必须 >= 2（`sys_set_priority` 的合法下限相同）
+pub const DEFAULT_PRIORITY: usize = 16;
+
 /// 计算给定的程序对应的内核栈的位置范围，返回 (bottom, top)，
 /// 主要是通过 TRAMPOLINE 和 KERNEL_STACK_SIZE 计算得到
 /// 
 /// 参数：
-/// - `app_id`: App 的 ID，范围是 0 到 MAX_APP_NUM - 1
-/// 
+/// - `app_id`: App 的 ID
+///
 /// 返回值：
 /// - `(usize, usize)`: 内核栈的底部和顶部地址
 pub fn kernel_stack_position(app_id: usize) -> (usize, usize) {
@@ -1,11 +1,22 @@
 #![no_main]
 #![no_std]
+#![feature(alloc_error_handler)]
 #![cfg(target_arch = "riscv64")]
+extern crate alloc;
+
 #[macro_use]
 mod lang_items;
+mod config;
 mod console;
-mod sbi;
+mod loader;
 mod logging;
+mod mm;
+mod sbi;
+mod sync;
+mod syscall;
+mod task;
+mod timer;
+mod trap;
 
 use core::arch::global_asm;
 use log::{trace, debug, info, warn, error};
@@ -60,7 +71,8 @@ pub extern "C" fn rust_main() -> ! {
     );
     error!("[kernel] .bss [{:#x}, {:#x})", sbss as usize, ebss as usize);
 
-    // CI autotest success: sbi::shutdown(false)
-    // CI autotest failed : sbi::shutdown(true)
-    sbi::shutdown(false)
+    mm::init();
+    trap::init();
+    task::add_initproc();
+    task::run_tasks();
 }
\ No newline at end of file
@@ -1,8 +1,12 @@
 use core::fmt::{self, Formatter, Debug};
 
 use crate::config::MEMORY_END;
-use crate::mm::address::{PhysPageNum, PhysAddr};
+use crate::mm::address::{PhysPageNum, PhysAddr, VirtPageNum};
+use crate::mm::page_table::PageTable;
+use crate::mm::swap::{swap_alloc_slot, swap_write};
 use crate::sync::UPSafeCell;
+use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::VecDeque;
 use alloc::vec::Vec;
 use lazy_static::lazy_static;
 use log::info;
@@ -10,6 +14,10 @@ use log::info;
 trait FrameAllocator {
     fn new() -> Self;
     fn alloc(&mut self) -> Option<PhysPageNum>;
+    /// 分配 `pages` 个物理上连续的页框，只从尚未使用过的 `[current, end)`
+    /// 区间中切出一段连续的前缀；即便 `recycled` 里躺着足够多的页框，
+    /// 只要它们不连续就返回 `None`，调用方可以按需退化为多次单页 `alloc`
+    fn alloc_more(&mut self, pages: usize) -> Option<Vec<PhysPageNum>>;
     fn dealloc(&mut self, ppn: PhysPageNum);
 }
 
@@ -17,6 +25,24 @@ pub struct StackFrameAllocator {
     current: PhysPageNum, // 空闲内存的起始物理页号
     end: PhysPageNum, // 空闲内存的结束物理页号
     recycled: Vec<PhysPageNum>,
+    /// 每个已分配物理页框的引用计数，供写时复制（COW）共享页面使用：
+    /// `frame_add_ref` 增加计数，`dealloc` 递减计数，只有减到 0 时才真正
+    /// 把这个物理页号放回 `recycled`
+    ref_counts: BTreeMap<PhysPageNum, usize>,
+    /// 可被驱逐的常驻页面组成的 FIFO 置换队列，物理内存耗尽时从队首取出牺牲者，
+    /// 参见 [`register_evictable`]
+    evict_queue: VecDeque<EvictEntry>,
+}
+
+/// 置换队列中的一项：记录一个可被驱逐的常驻页面归属于哪个页表、对应哪个虚拟页号，
+/// 以及它当前占据的物理页框
+///
+/// 调用方必须保证 `page_table` 指向的页表在这个页面被驱逐之前一直有效；
+/// 共享（COW）页面不会被登记进置换队列，因为驱逐它会破坏其他持有者的映射
+struct EvictEntry {
+    vpn: VirtPageNum,
+    ppn: PhysPageNum,
+    page_table: *mut PageTable,
 }
 
 pub struct FrameTracker {
@@ -31,6 +57,14 @@ impl FrameTracker {
         }
         Self { ppn }
     }
+
+    /// 创建一个与已有 `FrameTracker` 共享同一物理页框的新句柄，
+    /// 用于写时复制（COW）场景下父子进程共享同一块物理内存：
+    /// 不清零页面内容，只是把这个物理页框的引用计数加一
+    pub fn from_shared(ppn: PhysPageNum) -> Self {
+        frame_add_ref(ppn);
+        Self { ppn }
+    }
 }
 
 impl Debug for FrameTracker {
@@ -45,6 +79,42 @@ impl Drop for FrameTracker {
     }
 }
 
+/// `FrameTracker` 的连续多页版本：RAII 持有一段物理上连续的页框
+/// （由 [`frame_alloc_more`] 分配），`Drop` 时一次性归还整段区间
+pub struct FrameRangeTracker {
+    pub ppn_base: PhysPageNum,
+    pub pages: usize,
+}
+
+impl FrameRangeTracker {
+    fn new(ppns: Vec<PhysPageNum>) -> Self {
+        let ppn_base = ppns[0];
+        let pages = ppns.len();
+        for ppn in ppns.iter() {
+            for byte in ppn.get_bytes_array() {
+                *byte = 0;
+            }
+        }
+        Self { ppn_base, pages }
+    }
+}
+
+impl Debug for FrameRangeTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!(
+            "FrameRangeTracker:PPN=[{:#x}, {:#x})",
+            self.ppn_base.0,
+            self.ppn_base.0 + self.pages
+        ))
+    }
+}
+
+impl Drop for FrameRangeTracker {
+    fn drop(&mut self) {
+        frame_dealloc_more(self.ppn_base, self.pages);
+    }
+}
+
 
 type FrameAllocatorImpl = StackFrameAllocator;
 lazy_static! {
@@ -58,28 +128,55 @@ impl FrameAllocator for StackFrameAllocator {
             current: PhysPageNum(0),
             end: PhysPageNum(0),
             recycled: Vec::<PhysPageNum>::new(),
+            ref_counts: BTreeMap::new(),
+            evict_queue: VecDeque::new(),
         }
     }
 
     fn alloc(&mut self) -> Option<PhysPageNum> {
-        if let Some(ppn) = self.recycled.pop() {
+        let ppn = if let Some(ppn) = self.recycled.pop() {
+            Some(ppn)
+        } else if self.current != self.end {
+            let ppn = self.current;
+            self.current.0 += 1;
             Some(ppn)
         } else {
-            if self.current == self.end{
-                None
-            } else {
-                let ppn = self.current;
-                self.current.0 += 1;
-                Some(ppn)
-            }
+            self.evict_one()
+        }?;
+        self.ref_counts.insert(ppn, 1);
+        Some(ppn)
+    }
+
+    fn alloc_more(&mut self, pages: usize) -> Option<Vec<PhysPageNum>> {
+        if pages == 0 {
+            return Some(Vec::new());
+        }
+        if self.end.0 - self.current.0 < pages {
+            return None;
         }
+        let start = self.current;
+        self.current.0 += pages;
+        let range: Vec<PhysPageNum> = (start.0..self.current.0).map(PhysPageNum).collect();
+        for ppn in range.iter() {
+            self.ref_counts.insert(*ppn, 1);
+        }
+        Some(range)
     }
 
     fn dealloc(&mut self, ppn: PhysPageNum) {
         if ppn.0 >= self.current.0 || self.recycled.contains(&ppn) {
             panic!("Frame ppn={:#x} has not been allocated!", ppn.0);
         }
-        self.recycled.push(ppn);
+        let count = self
+            .ref_counts
+            .get_mut(&ppn)
+            .expect("Frame ppn has no reference count entry!");
+        *count -= 1;
+        if *count == 0 {
+            self.ref_counts.remove(&ppn);
+            self.evict_queue.retain(|entry| entry.ppn.0 != ppn.0);
+            self.recycled.push(ppn);
+        }
     }
 }
 
@@ -89,6 +186,43 @@ impl StackFrameAllocator {
         self.current = l;
         self.end = r;
     }
+
+    /// 把 `ppn` 的引用计数加一，供 COW 共享页面在父子进程间都持有一份
+    /// `FrameTracker` 句柄时使用
+    pub fn add_ref(&mut self, ppn: PhysPageNum) {
+        *self.ref_counts.entry(ppn).or_insert(0) += 1;
+    }
+
+    /// 查询 `ppn` 当前的引用计数，用于判断一次 COW 写错误是否需要真正拷贝页面
+    /// （计数 > 1）还是只需恢复写权限（计数 == 1）
+    pub fn ref_count(&self, ppn: PhysPageNum) -> usize {
+        *self.ref_counts.get(&ppn).unwrap_or(&0)
+    }
+
+    /// 物理内存耗尽时的最后手段：从置换队列队首取出最早登记的常驻页面作为牺牲者，
+    /// 把它的内容写入交换区，在它所属的页表中把这一页标记为"已换出"，
+    /// 然后把腾出来的物理页框直接交给新的分配请求
+    ///
+    /// 一个页面登记进队列之后完全可能被 `clone_cow` 设为 CoW 共享（`fork` 发生在
+    /// 它第一次被访问、注册为可驱逐之后），队列里的条目仍然只记录着最初那一侧的
+    /// `page_table`/`vpn`，驱逐它只会更新那一侧的页表项，另一侧的页表项却仍然指向
+    /// 这个物理页框——而这个页框会被立即清零交给别的分配请求，造成跨地址空间的
+    /// 内存破坏。因此每次取出候选者都要重新核对引用计数，计数 > 1 说明已经被共享，
+    /// 直接丢弃这个条目（不放回队列；如果之后又分裂成独占页面，也不会再被登记为
+    /// 可驱逐，换来的是安全而不是最优的置换策略），继续尝试下一个候选者
+    fn evict_one(&mut self) -> Option<PhysPageNum> {
+        loop {
+            let victim = self.evict_queue.pop_front()?;
+            if *self.ref_counts.get(&victim.ppn).unwrap_or(&0) > 1 {
+                continue;
+            }
+            let slot = swap_alloc_slot().expect("swap space exhausted, cannot evict any more pages");
+            swap_write(slot, victim.ppn.get_bytes_array());
+            let page_table = unsafe { &mut *victim.page_table };
+            page_table.mark_swapped(victim.vpn, slot);
+            return Some(victim.ppn);
+        }
+    }
 }
 
 pub fn init_frame_allocator() {
@@ -113,6 +247,48 @@ pub fn frame_dealloc(ppn: PhysPageNum) {
     FRAME_ALLOCATOR.exclusive_access().dealloc(ppn);
 }
 
+/// 分配 `pages` 个物理上连续的页框，返回一个统一管理整段区间的 `FrameRangeTracker`；
+/// 只有 `[current, end)` 里剩下连续的空闲区域足够大时才会成功，不会退化到拼凑
+/// 碎片化的 `recycled` 页框
+pub fn frame_alloc_more(pages: usize) -> Option<FrameRangeTracker> {
+    FRAME_ALLOCATOR
+        .exclusive_access()
+        .alloc_more(pages)
+        .map(FrameRangeTracker::new)
+}
+
+/// 归还一段由 `frame_alloc_more` 分配的连续页框，逐页走正常的 `dealloc` 路径
+/// （从而正确维护引用计数、置换队列等状态）
+pub fn frame_dealloc_more(ppn_base: PhysPageNum, pages: usize) {
+    let mut allocator = FRAME_ALLOCATOR.exclusive_access();
+    for i in 0..pages {
+        allocator.dealloc(PhysPageNum(ppn_base.0 + i));
+    }
+}
+
+/// 给一个已经分配的物理页框的引用计数加一，
+/// 用于写时复制（COW）场景下父子进程共享同一个物理页框
+pub fn frame_add_ref(ppn: PhysPageNum) {
+    FRAME_ALLOCATOR.exclusive_access().add_ref(ppn);
+}
+
+/// 查询一个物理页框当前的引用计数
+pub fn frame_ref_count(ppn: PhysPageNum) -> usize {
+    FRAME_ALLOCATOR.exclusive_access().ref_count(ppn)
+}
+
+/// 把一个常驻的页面登记为"可被驱逐"，加入 FIFO 置换队列：物理内存耗尽时，
+/// `frame_alloc` 会优先驱逐最早登记的页面，将它换出到交换区以腾出空间
+///
+/// 调用方必须保证 `page_table` 在这个页面被驱逐之前一直指向有效的页表；
+/// 共享（COW）页面不应当调用这个函数，驱逐它会破坏其他持有者的映射
+pub fn register_evictable(vpn: VirtPageNum, ppn: PhysPageNum, page_table: *mut PageTable) {
+    FRAME_ALLOCATOR
+        .exclusive_access()
+        .evict_queue
+        .push_back(EvictEntry { vpn, ppn, page_table });
+}
+
 
 #[allow(dead_code)]
 pub fn frame_allocator_test() {
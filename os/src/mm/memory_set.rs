@@ -1,6 +1,7 @@
 use alloc::{collections::btree_map::BTreeMap, vec::Vec};
+use lazy_static::lazy_static;
 use log::debug;
-use crate::{config::{MEMORY_END, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE}, mm::{address::{PhysPageNum, StepByOne, VPNRange, VirtAddr, VirtPageNum}, frame_allocator::{frame_alloc, FrameTracker}, page_table::{PTEFlags, PageTable}}};
+use crate::{config::{MEMORY_END, PAGE_SIZE, TRAMPOLINE, TRAP_CONTEXT, USER_STACK_SIZE}, mm::{address::{PhysAddr, PhysPageNum, StepByOne, VPNRange, VirtAddr, VirtPageNum}, frame_allocator::{frame_alloc, frame_ref_count, register_evictable, FrameTracker}, page_table::{PTEFlags, PageTable}, swap::{swap_free, swap_read}}, sync::UPSafeCell};
 
 // 定义了一些外部符号，这些符号通常是在链接阶段由链接器脚本定义的，
 // 用于标识内核映像中的特定段的起始和结束地址
@@ -56,6 +57,14 @@ pub struct MapArea {
     data_frames: BTreeMap<VirtPageNum, FrameTracker>,
     map_type: MapType,
     map_permission: MapPermission,
+    /// 为 `true` 时该逻辑段处于懒加载（demand paging）模式：
+    /// `push_lazy` 不会立即分配物理页框，而是等到第一次触发缺页异常时
+    /// 才由 `fault_in` 调用 `map_one` 按需建立映射
+    lazy: bool,
+    /// 懒加载逻辑段的数据来源：
+    /// - `Some(data)`：对应 ELF LOAD 段在 `input` 中的字节切片，缺页时据此逐页拷贝
+    /// - `None`：零填充段（用户栈、bss、堆等），缺页时只建立映射，内容保持全零
+    source: Option<&'static [u8]>,
 }
 
 impl MapArea {
@@ -79,9 +88,90 @@ impl MapArea {
             data_frames: BTreeMap::new(),
             map_type,
             map_permission,
+            lazy: false,
+            source: None,
         }
     }
 
+    /// 创建一个懒加载的 `Framed` 逻辑段：与 `new` 不同，这里不会立即分配物理页框，
+    /// 真正的映射与数据拷贝被推迟到缺页异常发生、`fault_in` 被调用时才发生
+    ///
+    /// 参数：
+    /// - `start_va`／`end_va`： 逻辑段的起始／结束虚拟地址
+    /// - `map_permission`： 逻辑段的权限
+    /// - `source`： 缺页时用来填充内容的数据来源，`None` 表示零填充
+    pub fn new_lazy(
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        map_permission: MapPermission,
+        source: Option<&'static [u8]>,
+    ) -> Self {
+        let start_vpn = start_va.floor();
+        let end_vpn = end_va.ceil();
+
+        Self {
+            vpn_range: VPNRange::new(start_vpn, end_vpn),
+            data_frames: BTreeMap::new(),
+            map_type: MapType::Framed,
+            map_permission,
+            lazy: true,
+            source,
+        }
+    }
+
+    fn contains_vpn(&self, vpn: VirtPageNum) -> bool {
+        vpn.0 >= self.vpn_range.get_start().0 && vpn.0 < self.vpn_range.get_end().0
+    }
+
+    /// 处理这个逻辑段内一次缺页异常：分配物理页框、建立映射，
+    /// 并在有数据来源时把对应的那一页内容拷贝进去
+    ///
+    /// 调用方必须先确认 `vpn` 落在 `vpn_range` 内，否则会 panic
+    pub fn fault_in(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        assert!(self.contains_vpn(vpn), "vpn {:?} is out of this area's range", vpn);
+        self.map_one(page_table, vpn);
+        if let Some(data) = self.source {
+            self.copy_one_page(page_table, vpn, data);
+        }
+    }
+
+    /// 处理这个逻辑段内一个写时复制（COW）页面上的写错误：
+    /// 如果这个物理页框还被其他地址空间共享（引用计数 > 1），分配一个新的物理
+    /// 页框、拷贝原有内容，再把这一页重新映射过去；如果已经是唯一持有者
+    /// （引用计数 == 1），说明另一侧已经完成了分裂，直接恢复 `W` 位即可
+    ///
+    /// 调用方必须保证 `vpn` 已经在 `data_frames` 中有对应的物理页框
+    fn resolve_cow_fault(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        let old_ppn = self.data_frames.get(&vpn).expect("cow page must already have a frame").ppn;
+        let flags = PTEFlags::from_bits(self.map_permission.bits() as usize).unwrap();
+        if frame_ref_count(old_ppn) > 1 {
+            let new_frame = frame_alloc().unwrap();
+            new_frame
+                .ppn
+                .get_bytes_array()
+                .copy_from_slice(old_ppn.get_bytes_array());
+            page_table.remap(vpn, new_frame.ppn, flags);
+            self.data_frames.insert(vpn, new_frame);
+        } else {
+            page_table.update_flags(vpn, flags);
+        }
+    }
+
+    /// 把一个之前被页面置换逻辑换出的页面从交换区换回来：分配一个新的物理页框、
+    /// 从对应的交换槽把内容拷贝回来、释放这个交换槽，再用这个逻辑段的权限重新
+    /// 建立映射，并把新页框重新登记为可被驱逐
+    ///
+    /// 调用方必须保证 `vpn` 对应的页表项确实处于"已换出"状态
+    fn swap_in(&mut self, page_table: &mut PageTable, vpn: VirtPageNum, slot: usize) {
+        let frame = frame_alloc().unwrap();
+        swap_read(slot, frame.ppn.get_bytes_array());
+        swap_free(slot);
+        let pte_flags = PTEFlags::from_bits(self.map_permission.bits() as usize).unwrap();
+        page_table.restore_swapped(vpn, frame.ppn, pte_flags);
+        register_evictable(vpn, frame.ppn, page_table as *mut PageTable);
+        self.data_frames.insert(vpn, frame);
+    }
+
     /// 映射一个虚拟页号到物理页框，
     /// 具体的实现是：
     /// 1. 根据映射类型分配物理页框，
@@ -107,6 +197,26 @@ impl MapArea {
         }
         let pte_flags = PTEFlags::from_bits(self.map_permission.bits() as usize).unwrap();
         page_table.map(vpn, ppn, pte_flags);
+        if self.map_type == MapType::Framed && !Self::is_unevictable(vpn) {
+            register_evictable(vpn, ppn, page_table as *mut PageTable);
+        }
+    }
+
+    /// 跳板页与 `TrapContext` 所在的区域（`[TRAP_CONTEXT, TRAMPOLINE]` 以上）
+    /// 永远不能被置换逻辑驱逐：trap 的返回路径需要随时能访问到它们
+    fn is_unevictable(vpn: VirtPageNum) -> bool {
+        let va: VirtAddr = vpn.into();
+        let addr: usize = va.into();
+        addr >= TRAP_CONTEXT
+    }
+
+    /// 这个逻辑段是否是内核直接通过物理地址读写的区域（目前只有 `TRAP_CONTEXT`）：
+    /// `get_trap_cx` 在内核自己的恒等映射下用 `ppn.get_bytes_array()` 直接访问它，
+    /// 从不经过这个区域所属用户地址空间的页表翻译，所以页表项的 `W` 位和缺页异常
+    /// 机制对它完全不起作用——`clone_cow` 不能把它做成 CoW 共享，必须立刻给子进程
+    /// 复制一份独立的物理页框
+    fn is_kernel_managed(&self) -> bool {
+        Self::is_unevictable(self.vpn_range.get_start())
     }
 
     /// 取消映射一个虚拟页号，
@@ -153,6 +263,26 @@ impl MapArea {
         }
     }
 
+    /// 将这个逻辑段的结尾扩展到 `new_end`（必须不小于当前的 `vpn_range.get_end()`），
+    /// 为新增的虚拟页号分配物理页框并建立映射，供 `sbrk` 增长堆时使用
+    pub fn append_to(&mut self, page_table: &mut PageTable, new_end: VirtPageNum) {
+        let old_end = self.vpn_range.get_end();
+        for vpn in VPNRange::new(old_end, new_end) {
+            self.map_one(page_table, vpn);
+        }
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), new_end);
+    }
+
+    /// 将这个逻辑段的结尾收缩到 `new_end`（必须不大于当前的 `vpn_range.get_end()`），
+    /// 取消被裁掉部分的映射并释放对应物理页框，供 `sbrk` 收缩堆时使用
+    pub fn shrink_to(&mut self, page_table: &mut PageTable, new_end: VirtPageNum) {
+        let old_end = self.vpn_range.get_end();
+        for vpn in VPNRange::new(new_end, old_end) {
+            self.unmap_one(page_table, vpn);
+        }
+        self.vpn_range = VPNRange::new(self.vpn_range.get_start(), new_end);
+    }
+
     /// 将数据从传入的数组切片拷贝到当前逻辑段映射到的物理内存中，
     /// 该复制过程确保了数据的正确对齐和分页处理：
     /// 切片 data 中的数据大小不超过当前逻辑段的总大小，
@@ -169,9 +299,7 @@ impl MapArea {
         let data_len = data.len();
 
         loop {
-            let src = &data[start..data_len.min(start+PAGE_SIZE)];
-            let dest = &mut page_table.translate(current_vpn).unwrap().ppn().get_bytes_array()[..src.len()];
-            dest.copy_from_slice(src);
+            self.copy_one_page(page_table, current_vpn, data);
             start += PAGE_SIZE;
             if start > data_len {
                 break;
@@ -179,6 +307,24 @@ impl MapArea {
             current_vpn.step();
         }
     }
+
+    /// 把 `data` 中落在 `vpn` 这一页范围内的那部分字节拷贝到 `vpn` 已映射的物理页框中，
+    /// 是 `copy_data`（一次性拷贝整段数据）与 `fault_in`（按需拷贝单页数据）共用的底层实现
+    ///
+    /// 参数：
+    /// - `page_table`： 页表，用来把 `vpn` 翻译为物理页框
+    /// - `vpn`： 需要被填充的虚拟页号，必须已经完成映射
+    /// - `data`： 该逻辑段的完整数据来源（例如 ELF LOAD 段的文件内容）
+    fn copy_one_page(&self, page_table: &PageTable, vpn: VirtPageNum, data: &[u8]) {
+        let page_start = (vpn.0 - self.vpn_range.get_start().0) * PAGE_SIZE;
+        if page_start >= data.len() {
+            return;
+        }
+        let page_end = data.len().min(page_start + PAGE_SIZE);
+        let src = &data[page_start..page_end];
+        let dest = &mut page_table.translate(vpn).unwrap().ppn().get_bytes_array()[..src.len()];
+        dest.copy_from_slice(src);
+    }
 }
 
 /// 内存集，代表一个完整的地址空间区域，
@@ -238,8 +384,310 @@ impl MemorySet {
         ), None);
     }
 
+    /// 向内存集中添加一个懒加载的逻辑段：与 `push` 不同，这里只是记录下这个逻辑段
+    /// 及其数据来源，既不建立页表映射也不分配物理页框，真正的映射发生在
+    /// 第一次访问触发缺页异常、`handle_page_fault` 找到这个逻辑段的时候
+    ///
+    /// 参数：
+    /// - `start_va`／`end_va`： 逻辑段的起始／结束虚拟地址
+    /// - `permission`： 逻辑段的权限
+    /// - `source`： 缺页时用来填充内容的数据来源，`None` 表示零填充（栈、bss、堆等）
+    pub fn push_lazy(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+        source: Option<&'static [u8]>,
+    ) {
+        self.areas.push(MapArea::new_lazy(start_va, end_va, permission, source));
+    }
+
+    /// 处理当前内存集中一次缺页异常，`is_write` 表示触发异常的访问是否是写操作
+    ///
+    /// 依次尝试两种合法的缺页场景：
+    /// - 如果这个虚拟页号还没有建立任何映射，且落在某个懒加载逻辑段内，就按需
+    ///   建立映射（并在有数据来源时拷贝数据）
+    /// - 如果这个虚拟页号已经有映射，但是是写操作、逻辑权限允许写、而当前页表项
+    ///   却没有 `W` 位，说明这是一个写时复制（COW）页面，按 COW 语义解决
+    ///
+    /// - 如果这个虚拟页号已经有映射，但页表项处于"已换出"状态（`V` 位清除、
+    ///   保存着交换槽编号），说明这一页之前被页面置换逻辑驱逐过，把它从交换区
+    ///   换回来
+    ///
+    /// 以上场景都不满足时返回 `false`，调用方（trap handler）应当将这视为
+    /// 一次真正的非法访问，杀死触发异常的任务
+    pub fn handle_page_fault(&mut self, vpn: VirtPageNum, is_write: bool) -> bool {
+        let Some(area_idx) = self.areas.iter().position(|area| area.contains_vpn(vpn)) else {
+            return false;
+        };
+
+        // 中间级页表已存在时，`translate` 对从未映射过的页面返回一个全零的页表项，
+        // 这里把它与"没有页表项"同等对待
+        match self
+            .page_table
+            .translate(vpn)
+            .filter(|pte| pte.is_valid() || pte.is_swapped())
+        {
+            None => {
+                if self.areas[area_idx].lazy {
+                    self.areas[area_idx].fault_in(&mut self.page_table, vpn);
+                    true
+                } else {
+                    false
+                }
+            }
+            Some(pte) if pte.is_swapped() => {
+                let slot = pte.swap_slot();
+                self.areas[area_idx].swap_in(&mut self.page_table, vpn, slot);
+                true
+            }
+            Some(pte) => {
+                let area = &mut self.areas[area_idx];
+                if is_write && area.map_permission.contains(MapPermission::W) && !pte.writable() {
+                    area.resolve_cow_fault(&mut self.page_table, vpn);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// 以写时复制（COW）的方式克隆出一个子地址空间，供 `fork` 使用：
+    /// 已经被访问过的 `Framed` 页面在父子之间共享同一块物理内存，
+    /// 双方的页表项都清除 `W` 位，真正发生写操作时再各自分裂出独立页面；
+    /// `Identical` 逻辑段（内核部分）本来就不需要写时复制，直接在子地址空间
+    /// 中重新建立同样的恒等映射；`TRAP_CONTEXT` 这类内核绕过用户页表、直接用
+    /// 物理地址读写的区域也不能共享——缺页异常机制对它们不起作用，必须立刻
+    /// 复制出独立页框（见 [`MapArea::is_kernel_managed`]）
+    pub fn clone_cow(&mut self) -> MemorySet {
+        let mut child = Self::new_bare();
+        child.map_trampoline();
+        let page_table = &mut self.page_table;
+        for area in self.areas.iter_mut() {
+            let mut new_area = MapArea {
+                vpn_range: area.vpn_range,
+                data_frames: BTreeMap::new(),
+                map_type: area.map_type,
+                map_permission: area.map_permission,
+                lazy: area.lazy,
+                source: area.source,
+            };
+            match area.map_type {
+                MapType::Identical => {
+                    new_area.map(&mut child.page_table);
+                }
+                MapType::Framed if area.is_kernel_managed() => {
+                    // TRAP_CONTEXT 这类区域被内核绕过用户页表直接读写，CoW 共享
+                    // 在这里没有意义也不安全：立刻给子进程分配独立页框并复制内容，
+                    // 父子双方都保留各自原有的完整读写权限
+                    let flags = PTEFlags::from_bits(area.map_permission.bits() as usize).unwrap();
+                    for (&vpn, frame) in area.data_frames.iter() {
+                        let new_frame = frame_alloc().unwrap();
+                        new_frame
+                            .ppn
+                            .get_bytes_array()
+                            .copy_from_slice(frame.ppn.get_bytes_array());
+                        child.page_table.map(vpn, new_frame.ppn, flags);
+                        new_area.data_frames.insert(vpn, new_frame);
+                    }
+                }
+                MapType::Framed => {
+                    let ro_flags = PTEFlags::from_bits(
+                        (area.map_permission - MapPermission::W).bits() as usize,
+                    )
+                    .unwrap();
+                    for (&vpn, frame) in area.data_frames.iter() {
+                        page_table.update_flags(vpn, ro_flags);
+                        child.page_table.map(vpn, frame.ppn, ro_flags);
+                        new_area
+                            .data_frames
+                            .insert(vpn, FrameTracker::from_shared(frame.ppn));
+                    }
+                }
+            }
+            child.areas.push(new_area);
+        }
+        child
+    }
+
+    /// 判断 `[start_vpn, end_vpn)` 是否与这个内存集中已有的任何逻辑段存在重叠
+    fn area_overlaps(&self, start_vpn: VirtPageNum, end_vpn: VirtPageNum) -> bool {
+        self.areas.iter().any(|area| {
+            start_vpn.0 < area.vpn_range.get_end().0 && area.vpn_range.get_start().0 < end_vpn.0
+        })
+    }
+
+    /// 从 `areas` 中找到起始虚拟页号恰好为 `start_vpn` 的逻辑段，
+    /// 取消它的全部映射并将其从内存集中移除
+    ///
+    /// 返回值：
+    /// - `true`：找到并成功移除了这样一个逻辑段
+    /// - `false`：不存在起始虚拟页号与 `start_vpn` 相符的逻辑段
+    pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) -> bool {
+        let Some(idx) = self
+            .areas
+            .iter()
+            .position(|area| area.vpn_range.get_start().0 == start_vpn.0)
+        else {
+            return false;
+        };
+        self.areas[idx].unmap(&mut self.page_table);
+        self.areas.remove(idx);
+        true
+    }
+
+    /// `mmap` 系统调用的核心实现：在 `[start_va, end_va)` 范围内插入一个新的
+    /// `Framed` 逻辑段，插入前会检查这个区间是否与已有的逻辑段重叠
+    ///
+    /// 返回值：
+    /// - `0`：成功插入
+    /// - `-1`：请求的区间与某个已有逻辑段重叠
+    pub fn mmap(&mut self, start_va: VirtAddr, end_va: VirtAddr, permission: MapPermission) -> isize {
+        if self.area_overlaps(start_va.floor(), end_va.ceil()) {
+            return -1;
+        }
+        self.insert_framed_area(start_va, end_va, permission);
+        0
+    }
+
+    /// `munmap` 系统调用的核心实现：要求 `[start_va, end_va)` 与某个已有逻辑段的
+    /// `vpn_range` 完全一致，否则视为非法调用
+    ///
+    /// 返回值：
+    /// - `0`：成功移除
+    /// - `-1`：不存在起止虚拟页号与请求区间精确匹配的逻辑段
+    pub fn munmap(&mut self, start_va: VirtAddr, end_va: VirtAddr) -> isize {
+        let start_vpn = start_va.floor();
+        let end_vpn = end_va.ceil();
+        let exists = self
+            .areas
+            .iter()
+            .any(|area| area.vpn_range.get_start().0 == start_vpn.0 && area.vpn_range.get_end().0 == end_vpn.0);
+        if !exists {
+            return -1;
+        }
+        if self.remove_area_with_start_vpn(start_vpn) {
+            0
+        } else {
+            -1
+        }
+    }
+
+    /// 将起始虚拟地址为 `start` 的逻辑段扩展到 `new_end`，用于 `sbrk` 增长堆空间
+    ///
+    /// 返回值：
+    /// - `true`：找到了这样一个逻辑段并完成了扩展
+    /// - `false`：不存在起始虚拟地址与 `start` 相符的逻辑段
+    pub fn append_to(&mut self, start: VirtAddr, new_end: VirtAddr) -> bool {
+        let start_vpn = start.floor();
+        let page_table = &mut self.page_table;
+        let Some(area) = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start().0 == start_vpn.0)
+        else {
+            return false;
+        };
+        area.append_to(page_table, new_end.ceil());
+        true
+    }
+
+    /// 将起始虚拟地址为 `start` 的逻辑段收缩到 `new_end`，用于 `sbrk` 收缩堆空间
+    ///
+    /// 返回值：
+    /// - `true`：找到了这样一个逻辑段并完成了收缩
+    /// - `false`：不存在起始虚拟地址与 `start` 相符的逻辑段
+    pub fn shrink_to(&mut self, start: VirtAddr, new_end: VirtAddr) -> bool {
+        let start_vpn = start.floor();
+        let page_table = &mut self.page_table;
+        let Some(area) = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start().0 == start_vpn.0)
+        else {
+            return false;
+        };
+        area.shrink_to(page_table, new_end.ceil());
+        true
+    }
+
+    /// 返回这个内存集对应的 satp 寄存器取值：SV39 模式号 8 位于最高 4 位，
+    /// 低位是根页表的物理页号
+    pub fn token(&self) -> usize {
+        self.page_table.token()
+    }
+
+    /// 将虚拟页号翻译为这个内存集页表中对应的页表项
+    pub fn translate(&self, vpn: VirtPageNum) -> Option<crate::mm::page_table::PageTableEntry> {
+        self.page_table.translate(vpn)
+    }
+
+    /// 校验 `[start, end)` 是否整段落在这个地址空间已登记的逻辑段内，且具备
+    /// `U` 权限以及 `need_write` 要求的读/写权限；期间按需对懒加载但尚未
+    /// 建立映射的页面触发 `handle_page_fault` 补齐映射，其行为与用户态真正
+    /// 访问这段内存时触发的缺页异常完全一致
+    ///
+    /// 系统调用在把用户态指针交给 `translated_byte_buffer`/`translated_str`
+    /// 翻译、解引用之前都应该先调用这个函数，否则一个指向未映射地址的指针
+    /// 会导致 `PageTable::translate` 返回 `None`，进而在 `.unwrap()` 处让内核直接 panic
+    /// 返回值:
+    /// - `true`：`[start, end)` 合法且已经可以安全解引用
+    /// - `false`：`start` 所在页号大于 `end` 向上取整后的页号，或者这段地址有一部分没有落在任何逻辑段内，
+    ///   或者权限不足（比如试图写一个只读段）
+    pub fn validate_user_range(&mut self, start: VirtAddr, end: VirtAddr, need_write: bool) -> bool {
+        let start_vpn = start.floor();
+        let end_vpn = end.ceil();
+        if start_vpn.0 > end_vpn.0 {
+            return false;
+        }
+        for vpn in VPNRange::new(start_vpn, end_vpn) {
+            let Some(area_idx) = self.areas.iter().position(|area| area.contains_vpn(vpn)) else {
+                return false;
+            };
+            let permission = self.areas[area_idx].map_permission;
+            if !permission.contains(MapPermission::U) {
+                return false;
+            }
+            if need_write && !permission.contains(MapPermission::W) {
+                return false;
+            }
+            if !need_write && !permission.contains(MapPermission::R) {
+                return false;
+            }
+            if self.page_table.translate(vpn).is_none_or(|pte| !pte.is_valid()) {
+                if !self.handle_page_fault(vpn, need_write) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// 把跳板页映射到 `TRAMPOLINE` 这个固定的虚拟地址上：物理页号就是 `strampoline`
+    /// 所在的那一页，权限只有 `R|X`（不含 `U`，用户态不能直接访问）。这是一个
+    /// 不追踪、不受置换逻辑管理的单页映射（虚拟页号与物理页号并不相同）——每个地址空间都需要在同一个虚拟地址
+    /// 上看到同一份跳板代码，才能在 `__switch`/`trap_return` 切换页表前后
+    /// 保持指令流的连续执行
     pub fn map_trampoline(&mut self) {
-        todo!()
+        self.page_table.map(
+            VirtAddr::from(TRAMPOLINE).floor(),
+            PhysAddr::from(strampoline as usize).floor(),
+            PTEFlags::R | PTEFlags::X,
+        );
+    }
+
+    /// 把这个内存集的页表安装进 `satp` 寄存器并刷新整个 TLB，
+    /// 使后续的地址翻译切换到这个内存集代表的地址空间
+    ///
+    /// 调用方需要保证跳板页已经映射在同样的虚拟地址上，否则这条 `activate`
+    /// 指令执行完的下一条指令就会因为取指失败而陷入异常
+    pub fn activate(&self) {
+        let satp = self.token();
+        unsafe {
+            riscv::register::satp::write(satp);
+            core::arch::asm!("sfence.vma");
+        }
     }
 
     /// 创建一个新的内核内存集，
@@ -346,15 +794,23 @@ impl MemorySet {
     ///    - .rodata 段：只读数据段，权限为 U+R
     ///    - .text 段：代码段，权限为 U+R+X
     ///
+    /// 取代了 `loader.rs` 里那套把每个应用当作扁平字节镜像、`objcopy` 成
+    /// `.bin` 再复制到固定基址的旧方案：这里直接按 ELF 格式解析 `PT_LOAD`
+    /// 程序头，每个段按自己的 `p_vaddr`/`file_size`/`mem_size` 建立独立的
+    /// 懒加载 `Framed` 逻辑段（`[file_size, mem_size)` 的尾部由缺页时 `fault_in`
+    /// 分配到的全零页框自然实现 .bss 清零），应用程序也因此可以在链接时声明
+    /// 任意自己的入口地址和段布局，不再要求所有应用共享同一个基址
+    ///
     /// 参数：
-    /// - `elf_data`： ELF 文件的原始字节数据
+    /// - `elf_data`： ELF 文件的原始字节数据，要求 `'static` 生命周期，
+    ///   因为下面懒加载的 LOAD 段需要把这份切片一直保存到缺页异常发生时才使用
     ///
     /// 返回值：
     /// - `(Self, usize, usize)`：
     ///   - 第一个值：构建好的用户态内存集
     ///   - 第二个值：用户栈顶地址
     ///   - 第三个值：应用程序入口点地址
-    pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
+    pub fn from_elf(elf_data: &'static [u8]) -> (Self, usize, usize) {
         // 创建一个新的空内存集
         let mut memory_set = Self::new_bare();
         // 映射跳板页，用于在用户态和内核态之间切换
@@ -393,19 +849,15 @@ impl MemorySet {
                     map_permission |= MapPermission::X;
                 }
 
-                // 创建逻辑段，使用 Framed 映射类型
-                let map_area = MapArea::new(
+                // 更新最大结束虚拟页号
+                max_end_vpn = VPNRange::new(start_va.floor(), end_va.ceil()).get_end();
+                // 懒加载这个 LOAD 段：先不分配物理页框，等第一次访问触发缺页异常时
+                // 再按需建立映射并从 ELF 数据中拷贝对应的那一页
+                memory_set.push_lazy(
                     start_va,
                     end_va,
-                    MapType::Framed,
-                    map_permission
-                );
-                // 更新最大结束虚拟页号
-                max_end_vpn = map_area.vpn_range.get_end();
-                // 将段添加到内存集中，并复制 ELF 文件中的数据
-                memory_set.push(
-                    map_area,
-                    Some(&elf.input[ph.offset() as usize..(ph.offset()+ph.file_size()) as usize])
+                    map_permission,
+                    Some(&elf_data[ph.offset() as usize..(ph.offset()+ph.file_size()) as usize])
                 );
             }
         }
@@ -417,13 +869,13 @@ impl MemorySet {
         user_stack_bottom += PAGE_SIZE;
         let user_stack_top = user_stack_bottom + USER_STACK_SIZE;
 
-        // 映射用户栈，权限为 U+R+W
-        memory_set.push(MapArea::new(
+        // 懒加载用户栈，权限为 U+R+W：缺页时零填充
+        memory_set.push_lazy(
             user_stack_bottom.into(),
             user_stack_top.into(),
-            MapType::Framed,
-            MapPermission::R | MapPermission::W | MapPermission::U
-        ), None);
+            MapPermission::R | MapPermission::W | MapPermission::U,
+            None,
+        );
 
         // 在用户栈顶创建一个零长度的映射，用于 sbrk 系统调用的堆空间管理
         // 这个映射标记了堆的起始位置，后续可以通过 sbrk 扩展堆空间
@@ -445,4 +897,17 @@ impl MemorySet {
         // 返回内存集、用户栈顶地址和应用程序入口点地址
         (memory_set, user_stack_top, elf.header.pt2.entry_point() as usize)
     }
+}
+
+lazy_static! {
+    /// 全局唯一的内核地址空间：包含内核代码段、数据段以及整块可用物理内存的恒等映射，
+    /// 各个任务自己的内核栈也以 `Framed` 逻辑段的形式动态插入这个地址空间，
+    /// 供陷入内核态后运行在独立内核栈上的代码使用
+    pub static ref KERNEL_SPACE: UPSafeCell<MemorySet> =
+        unsafe { UPSafeCell::new(MemorySet::new_kernel()) };
+}
+
+/// 内核地址空间对应的 satp 取值，供需要临时切换回内核页表的场景使用
+pub fn kernel_token() -> usize {
+    KERNEL_SPACE.exclusive_access().token()
 }
\ No newline at end of file
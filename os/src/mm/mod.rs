@@ -4,10 +4,25 @@ mod heap_allocator;
 mod address;
 mod page_table;
 mod frame_allocator;
+mod memory_set;
+mod swap;
 
+pub use address::{PhysAddr, PhysPageNum, StepByOne, VPNRange, VirtAddr, VirtPageNum};
+pub use frame_allocator::{
+    frame_alloc, frame_alloc_more, frame_add_ref, frame_dealloc_more, frame_ref_count,
+    FrameRangeTracker, FrameTracker,
+};
+pub use memory_set::{kernel_token, MapArea, MapPermission, MapType, MemorySet, KERNEL_SPACE};
+pub use page_table::{
+    translated_byte_buffer, translated_str, PageTable, PageTableEntry, UserBuffer, PTEFlags,
+};
+
+/// 内存管理子系统的初始化入口：依次建立内核堆、物理页框分配器，
+/// 最后把内核地址空间的页表装进 `satp`，使内核自身也运行在分页机制之下
 pub fn init() {
     heap_allocator::init_heap();
     frame_allocator::init_frame_allocator();
+    KERNEL_SPACE.exclusive_access().activate();
 }
 
 #[allow(dead_code)]
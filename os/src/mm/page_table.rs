@@ -62,6 +62,25 @@ impl PageTableEntry {
     pub fn executable(&self) -> bool {
         (self.flags() & PTEFlags::X) != PTEFlags::empty()
     }
+
+    /// 构造一个表示"已被换出"的页表项：`V` 位保持清除，交换槽编号（加一后）被编码
+    /// 进原本存放物理页号的高位中，换入时由 `swap_slot` 取出
+    pub fn new_swapped(slot: usize) -> Self {
+        Self {
+            bits: (slot + 1) << 10,
+        }
+    }
+
+    /// 页表项是否代表一个已被换出、内容保存在交换区里的页面：
+    /// `V` 位清除，但 `bits` 不为零（区别于从未建立过映射的空页表项）
+    pub fn is_swapped(&self) -> bool {
+        self.bits != 0 && !self.is_valid()
+    }
+
+    /// 取出一个已换出页表项中保存的交换槽编号
+    pub fn swap_slot(&self) -> usize {
+        (self.bits >> 10) - 1
+    }
 }
 
 impl PageTable {
@@ -74,13 +93,24 @@ impl PageTable {
     }
 
     fn find_pte_create(&mut self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
+        self.find_pte_create_at_level(vpn, 2)
+    }
+
+    /// 与 [`find_pte_create`] 相同，但在 `leaf_level` 这一级（0 = 1 GiB 页表根，
+    /// 1 = 2 MiB 的二级页表，2 = 普通的 4 KiB 叶子）就停止下降，
+    /// 把这一级的页表项当作叶子返回，供 [`map_gib`]/[`map_mib`] 建立大页映射
+    fn find_pte_create_at_level(
+        &mut self,
+        vpn: VirtPageNum,
+        leaf_level: usize,
+    ) -> Option<&mut PageTableEntry> {
         let indecies = vpn.indecies();
         let mut curr_table_root_ppn = self.root_pfn;
         let mut ans: Option<&mut PageTableEntry> = None;
 
         for i in 0..3 {
             let curr_pte = &mut curr_table_root_ppn.get_pte_array()[indecies[i]];
-            if i == 2 {
+            if i == leaf_level {
                 ans = Some(curr_pte);
                 break;
             }
@@ -95,6 +125,9 @@ impl PageTable {
         ans
     }
 
+    /// 查找 `vpn` 对应的页表项，提前识别"大页叶子"：只要中间级的页表项已经是
+    /// 有效的，并且设置了 R/W/X 中的任意一位（区别于指向下一级页表的普通表项），
+    /// 就说明它本身就是一个 2 MiB/1 GiB 大页的叶子，不需要（也不能）继续下降
     fn find_pte(&self, vpn: VirtPageNum) -> Option<&mut PageTableEntry> {
         let indecies = vpn.indecies();
         let mut curr_table_root_ppn = self.root_pfn;
@@ -102,7 +135,7 @@ impl PageTable {
 
         for i in 0..3 {
             let curr_pte = &mut curr_table_root_ppn.get_pte_array()[indecies[i]];
-            if i == 2 {
+            if i == 2 || is_leaf(curr_pte) {
                 ans = Some(curr_pte);
                 break;
             }
@@ -121,6 +154,31 @@ impl PageTable {
         *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
     }
 
+    /// 建立一个 1 GiB 的大页映射：`vpn`/`ppn` 必须在 1 GiB 边界上对齐
+    /// （`indecies()` 的低两级索引均为 0），`flags` 必须至少设置 R/W/X 中的一位，
+    /// 使这个根级页表项本身就是叶子，而不是指向下一级页表
+    pub fn map_gib(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        assert!(
+            flags.intersects(PTEFlags::R | PTEFlags::W | PTEFlags::X),
+            "map_gib requires at least one of R/W/X to form a leaf PTE"
+        );
+        let pte = self.find_pte_create_at_level(vpn, 0).unwrap();
+        assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+
+    /// 建立一个 2 MiB 的大页映射：`vpn`/`ppn` 必须在 2 MiB 边界上对齐
+    /// （`indecies()` 的最低一级索引为 0），`flags` 必须至少设置 R/W/X 中的一位
+    pub fn map_mib(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        assert!(
+            flags.intersects(PTEFlags::R | PTEFlags::W | PTEFlags::X),
+            "map_mib requires at least one of R/W/X to form a leaf PTE"
+        );
+        let pte = self.find_pte_create_at_level(vpn, 1).unwrap();
+        assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+
     pub fn unmap(&mut self, vpn: VirtPageNum){
         if let Some(pte) = self.find_pte(vpn) {
             assert!(pte.is_valid(), "vpn {:?} is invalid before unmapping", vpn);
@@ -130,6 +188,41 @@ impl PageTable {
         }
     }
 
+    /// 就地修改一个已经存在的页表项的权限位，保留它指向的物理页号不变，
+    /// 用于写时复制（COW）场景下在父子进程之间清除/恢复 `W` 位
+    pub fn update_flags(&mut self, vpn: VirtPageNum, flags: PTEFlags) {
+        let pte = self.find_pte(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is invalid before updating flags", vpn);
+        let ppn = pte.ppn();
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+
+    /// 把一个已经存在的页表项重新指向另一个物理页号，
+    /// 用于写时复制（COW）页面在真正发生写操作时分裂出独立的物理页框
+    pub fn remap(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        let pte = self.find_pte(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is invalid before remapping", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+
+    /// 把一个常驻页面的页表项标记为"已换出"：保存交换槽编号并清除 `V` 位，
+    /// 供页面置换逻辑在驱逐一个常驻页面时调用
+    pub fn mark_swapped(&mut self, vpn: VirtPageNum, slot: usize) {
+        let pte = self.find_pte(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is not resident before marking swapped", vpn);
+        *pte = PageTableEntry::new_swapped(slot);
+        flush_tlb(vpn);
+    }
+
+    /// 把一个因被换出而失效的页表项重新指向新分配的物理页框，恢复原来的访问权限，
+    /// 供页面置换逻辑在缺页异常中把内容换入后调用
+    pub fn restore_swapped(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        let pte = self.find_pte(vpn).unwrap();
+        assert!(pte.is_swapped(), "vpn {:?} is not a swapped-out page", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+        flush_tlb(vpn);
+    }
+
     pub fn from_token(satp: usize) -> Self {
         Self {
             root_pfn: PhysPageNum::from(satp & ((1usize << 44) - 1)),
@@ -147,6 +240,51 @@ impl PageTable {
     }
 }
 
+/// 判断一个页表项是否是"大页叶子"：有效，并且设置了 R/W/X 中的任意一位。
+/// 普通的、指向下一级页表的页表项只会设置 V 位，不会设置 R/W/X
+fn is_leaf(pte: &PageTableEntry) -> bool {
+    pte.is_valid() && (pte.readable() || pte.writable() || pte.executable())
+}
+
+/// 刷新 `vpn` 这一页对应的 TLB 表项，避免页表项被置换逻辑改写之后，
+/// 旧的虚实地址翻译仍然残留在 TLB 中
+fn flush_tlb(vpn: VirtPageNum) {
+    let va: VirtAddr = vpn.into();
+    let addr: usize = va.into();
+    unsafe {
+        core::arch::asm!("sfence.vma {0}, x0", in(reg) addr);
+    }
+}
+
+/// 从用户态地址空间中读取一个以 NUL 结尾的字符串，逐字节翻译直至遇到 `\0`
+///
+/// 每个字节在读取前都会先检查它所在页的页表项存在、有效、具备 `U`/`R` 权限，
+/// 而不是像翻译合法指针时那样直接 `.unwrap()`——`ptr` 来自用户态，一个指向
+/// 未映射地址或内核专属页面的恶意指针不应该让内核 panic
+/// 返回值:
+/// - `Some(string)`：整段字符串都落在调用者可读的用户页面内
+/// - `None`：扫描到 NUL 之前先碰到了一个未映射、或不可被用户态读取的页面
+pub fn translated_str(token: usize, ptr: *const u8) -> Option<alloc::string::String> {
+    let page_table = PageTable::from_token(token);
+    let mut string = alloc::string::String::new();
+    let mut va = ptr as usize;
+    loop {
+        let va_floor = VirtAddr::from(va).floor();
+        let page_offset = VirtAddr::from(va).page_offset();
+        let pte = page_table.translate(va_floor)?;
+        if !pte.is_valid() || !pte.readable() || !pte.flags().contains(PTEFlags::U) {
+            return None;
+        }
+        let ch = pte.ppn().get_bytes_array()[page_offset];
+        if ch == 0 {
+            break;
+        }
+        string.push(ch as char);
+        va += 1;
+    }
+    Some(string)
+}
+
 pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&'static mut [u8]> {
     let page_table = PageTable::from_token(token);
     let mut start = ptr as usize;
@@ -171,4 +309,61 @@ pub fn translated_byte_buffer(token: usize, ptr: *const u8, len: usize) -> Vec<&
         start = end_va.into();
     }
     v
+}
+
+/// 对 `translated_byte_buffer` 返回的一组分散物理字节切片的封装，让内核代码
+/// 可以把用户缓冲区当作一个逻辑上连续的字节序列来读写，而不必关心它实际上
+/// 跨越了多少个（可能不相邻的）物理页框
+pub struct UserBuffer {
+    pub buffers: Vec<&'static mut [u8]>,
+}
+
+impl UserBuffer {
+    pub fn new(buffers: Vec<&'static mut [u8]>) -> Self {
+        Self { buffers }
+    }
+
+    /// 所有分散切片的长度之和，即这个用户缓冲区总共覆盖的字节数
+    pub fn len(&self) -> usize {
+        self.buffers.iter().map(|b| b.len()).sum()
+    }
+}
+
+impl IntoIterator for UserBuffer {
+    type Item = *mut u8;
+    type IntoIter = UserBufferIterator;
+
+    fn into_iter(self) -> Self::IntoIter {
+        UserBufferIterator {
+            buffers: self.buffers,
+            current_buffer: 0,
+            current_idx: 0,
+        }
+    }
+}
+
+/// `UserBuffer` 的字节迭代器，按顺序产出每一个字节的裸指针，
+/// 让调用方可以逐字节写入（跨越切片边界时自动前进到下一个切片）
+pub struct UserBufferIterator {
+    buffers: Vec<&'static mut [u8]>,
+    current_buffer: usize,
+    current_idx: usize,
+}
+
+impl Iterator for UserBufferIterator {
+    type Item = *mut u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_buffer >= self.buffers.len() {
+            return None;
+        }
+        let r = &mut self.buffers[self.current_buffer][self.current_idx] as *mut u8;
+        if self.current_idx + 1 == self.buffers[self.current_buffer].len() {
+            self.current_idx = 0;
+            self.current_buffer += 1;
+        } else {
+            self.current_idx += 1;
+        }
+        Some(r)
+    }
 }
\ No newline at end of file
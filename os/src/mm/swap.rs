@@ -0,0 +1,67 @@
+//! 交换区（backing store）：物理内存耗尽时，页面置换逻辑把被驱逐的常驻页面内容
+//! 暂存在这里，换入时再按交换槽编号把内容读回来
+
+use crate::config::PAGE_SIZE;
+use crate::sync::UPSafeCell;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+
+/// 交换区能够容纳的页面数量
+const SWAP_SLOTS: usize = 64;
+
+/// 交换区管理器，用一组定长字节数组模拟磁盘上的交换分区，
+/// `free` 以栈的形式记录尚未被占用的槽位编号
+struct SwapManager {
+    storage: Vec<[u8; PAGE_SIZE]>,
+    free: Vec<usize>,
+}
+
+impl SwapManager {
+    fn new() -> Self {
+        Self {
+            storage: (0..SWAP_SLOTS).map(|_| [0u8; PAGE_SIZE]).collect(),
+            free: (0..SWAP_SLOTS).rev().collect(),
+        }
+    }
+
+    fn alloc(&mut self) -> Option<usize> {
+        self.free.pop()
+    }
+
+    fn write(&mut self, slot: usize, data: &[u8]) {
+        self.storage[slot].copy_from_slice(data);
+    }
+
+    fn read(&self, slot: usize, dest: &mut [u8]) {
+        dest.copy_from_slice(&self.storage[slot]);
+    }
+
+    fn free_slot(&mut self, slot: usize) {
+        self.free.push(slot);
+    }
+}
+
+lazy_static! {
+    static ref SWAP_MANAGER: UPSafeCell<SwapManager> =
+        unsafe { UPSafeCell::new(SwapManager::new()) };
+}
+
+/// 在交换区中分配一个空闲槽位，用于存放即将被换出的页面内容
+pub fn swap_alloc_slot() -> Option<usize> {
+    SWAP_MANAGER.exclusive_access().alloc()
+}
+
+/// 把一页内容（必须恰好 `PAGE_SIZE` 字节）写入交换区中的 `slot` 槽位
+pub fn swap_write(slot: usize, data: &[u8]) {
+    SWAP_MANAGER.exclusive_access().write(slot, data);
+}
+
+/// 从交换区中的 `slot` 槽位把一页内容读回 `dest`（必须恰好 `PAGE_SIZE` 字节）
+pub fn swap_read(slot: usize, dest: &mut [u8]) {
+    SWAP_MANAGER.exclusive_access().read(slot, dest);
+}
+
+/// 释放交换区中的 `slot` 槽位，使其可以被后续换出的页面复用
+pub fn swap_free(slot: usize) {
+    SWAP_MANAGER.exclusive_access().free_slot(slot);
+}
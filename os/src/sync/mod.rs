@@ -0,0 +1,3 @@
+mod up;
+
+pub use up::UPSafeCell;
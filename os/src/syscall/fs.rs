@@ -1,29 +1,86 @@
 //! File and filesystem-related syscalls
+use crate::mm::{translated_byte_buffer, UserBuffer};
 use crate::print;
+use crate::task::{current_user_token, current_validate_user_range, suspend_current_and_run_next};
+
+const FD_STDIN: usize = 0;
 const FD_STDOUT: usize = 1;
 
 /// write 的 System Call 实现，本质上是对于 console::print 的封装
 /// 目前仅支持向标准输出（fd=1）写入
 /// 参数:
 /// - fd: 文件描述符
-/// - buf: 数据缓冲区指针
+/// - buf: 数据缓冲区指针，是一个*用户态*地址，必须先经过当前任务的页表翻译
+///   才能在内核中安全访问
 /// - len: 写入数据的长度
 /// 返回值:
 /// - 成功时返回写入的字节数
-/// - 失败时触发 panic
+/// - `fd` 不是标准输出，或者 `[buf, buf+len)` 没有整段落在调用者可读的用户页面内时返回 -1
 /// 注意:
-/// - 该函数假设 buf 指向的内存区域是有效且可读
-/// - 仅支持 fd=1 (标准输出)，其他 fd 会触发 panic
+/// - `buf` 对应的内存可能跨越多个物理页框（甚至不连续），因此通过
+///   `translated_byte_buffer` 把它翻译成一组物理页内的字节切片，再逐段打印
+/// - 仅支持 fd=1 (标准输出)，在解引用 `buf` 之前先校验它的整段地址区间，
+///   避免一个越界或指向内核页面的 `buf` 让内核直接 panic
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
     match fd {
         FD_STDOUT => {
-            let slice = unsafe { core::slice::from_raw_parts(buf, len) };
-            let str = core::str::from_utf8(slice).unwrap();
-            print!("{}", str);
+            if !current_validate_user_range(buf as usize, len, false) {
+                return -1;
+            }
+            let buffers = translated_byte_buffer(current_user_token(), buf, len);
+            for buffer in buffers {
+                let str = core::str::from_utf8(buffer).unwrap();
+                print!("{}", str);
+            }
             len as isize
         }
-        _ => {
-            panic!("Unsupported fd in sys_write!");
+        _ => -1,
+    }
+}
+
+/// read 的 System Call 实现，目前仅支持从标准输入（fd=0）读取一个字节
+/// 参数:
+/// - fd: 文件描述符
+/// - buf: 用户态地址，读取到的字节写回这里，必须先经过当前任务的页表翻译
+/// - len: 期望读取的字节数，目前固定只支持 1
+/// 返回值:
+/// - 成功时返回实际读取的字节数（恒为 1）
+/// - `fd` 不是标准输入、`len` 不是 1，或者 `buf` 没有落在调用者可写的用户页面内时
+///   返回 -1
+/// 注意:
+/// - stdin 通过 SBI 的 `console_getchar` 轮询获取字符，还没有字符输入时
+///   返回值是 -1，此时通过 `suspend_current_and_run_next` 让出 CPU 再重试，
+///   从而实现阻塞式的行输入而不是忙等
+/// - 仅支持 fd=0 (标准输入)，在写 `buf` 之前先校验它的地址，避免一个
+///   越界或指向只读/内核页面的 `buf` 让内核直接 panic
+pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
+    match fd {
+        FD_STDIN => {
+            if len != 1 {
+                return -1;
+            }
+            if !current_validate_user_range(buf as usize, len, true) {
+                return -1;
+            }
+            let mut c: usize;
+            loop {
+                c = sbi_rt::legacy::console_getchar();
+                if c as isize == -1 {
+                    suspend_current_and_run_next();
+                    continue;
+                } else {
+                    break;
+                }
+            }
+            let ch = c as u8;
+            let buffers = UserBuffer::new(translated_byte_buffer(current_user_token(), buf, len));
+            if let Some(byte_ptr) = buffers.into_iter().next() {
+                unsafe {
+                    *byte_ptr = ch;
+                }
+            }
+            1
         }
+        _ => -1,
     }
 }
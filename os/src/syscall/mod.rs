@@ -0,0 +1,54 @@
+//! System call dispatch: decodes the syscall id carried in `a7` and routes it
+//! to the matching handler in `fs`/`process`
+
+mod fs;
+mod process;
+
+use fs::{sys_read, sys_write};
+use process::{
+    sys_exec, sys_exit, sys_fork, sys_get_time, sys_getpid, sys_mmap, sys_munmap, sys_sbrk,
+    sys_set_priority, sys_sleep, sys_waitpid, sys_yield,
+};
+
+const SYSCALL_READ: usize = 63;
+const SYSCALL_WRITE: usize = 64;
+const SYSCALL_EXIT: usize = 93;
+const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_SBRK: usize = 214;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
+
+/// 根据系统调用号 `syscall_id` 分发到具体的系统调用实现
+/// 参数:
+/// - `syscall_id`: 系统调用号，来自用户态 `ecall` 时 `a7`（`x17`）寄存器的值
+/// - `args`: 系统调用的参数，最多 3 个，依次对应 `a0`~`a2`（`x10`~`x12`）寄存器
+/// 返回值:
+/// - 对应系统调用的返回值
+/// # Panics
+/// 如果 `syscall_id` 不是上面列出的已知系统调用号，则会 panic
+pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
+    match syscall_id {
+        SYSCALL_READ => sys_read(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_WRITE => sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_SLEEP => sys_sleep(args[0]),
+        SYSCALL_GET_TIME => sys_get_time(),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_SBRK => sys_sbrk(args[0] as isize),
+        SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_FORK => sys_fork(),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
+        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+    }
+}
@@ -1,17 +1,29 @@
 //! App management syscalls
-use crate::{println, task::{exit_current_and_run_next, suspend_current_and_run_next}};
+use crate::{
+    config::PAGE_SIZE,
+    loader::get_app_data_by_name,
+    mm::{translated_byte_buffer, translated_str, MapPermission},
+    println,
+    task::{
+        add_task, change_program_brk, current_mmap, current_munmap, current_set_priority,
+        current_sleep_until, current_task, current_user_token, exit_current_and_run_next,
+        suspend_current_and_run_next,
+    },
+    timer::get_time_ms,
+};
+use alloc::sync::Arc;
 
 /// exit 的 System Call 实现
 /// 参数:
 /// - exit_code: 应用程序的退出码
 /// 返回值:
-/// - 该函数不会返回，调用后会切换到下一个应用程序
+/// - 该函数不会返回，调用后会把当前任务标记为僵尸状态并切换到下一个任务
 /// 注意:
 /// - 该函数会打印应用程序的退出码
-/// - 该函数假设当前有下一个应用程序可运行，当没有下一个应用程序运行时会关机
+/// - 退出码被记录在任务控制块中，等待父进程通过 `waitpid` 回收
 pub fn sys_exit(exit_code: i32) -> ! {
     println!("[kernel] Application exited with code {}", exit_code);
-    exit_current_and_run_next();
+    exit_current_and_run_next(exit_code);
     panic!("Unreachable in sys_exit!"); // 这一行理论上不会被执行
 }
 
@@ -19,3 +31,199 @@ pub fn sys_yield() -> isize {
     suspend_current_and_run_next();
     0
 }
+
+/// getpid 的 System Call 实现
+/// 返回值:
+/// - 当前任务的 PID
+pub fn sys_getpid() -> isize {
+    current_task().unwrap().pid() as isize
+}
+
+/// fork 的 System Call 实现：以写时复制的方式复制当前任务的地址空间和陷入上下文，
+/// 创建一个新的子任务并放入就绪队列
+/// 返回值:
+/// - 在父进程中返回子进程的 PID
+/// - 在子进程中返回 0（由 `TaskControlBlock::fork` 负责把子进程陷入上下文里的 a0 清零）
+pub fn sys_fork() -> isize {
+    let current_task = current_task().unwrap();
+    let new_task = current_task.fork();
+    let new_pid = new_task.pid();
+    add_task(new_task);
+    new_pid as isize
+}
+
+/// exec 的 System Call 实现：按 `path` 指向的名字通过 `get_app_data_by_name` 找到对应的
+/// ELF 数据，替换当前任务的地址空间
+/// 参数:
+/// - path: 指向用户态以 NUL 结尾的应用程序名称字符串的指针
+/// 返回值:
+/// - 成功时该系统调用不会真正返回（地址空间已被替换，从新的入口点开始执行）
+/// - 找不到同名应用程序时返回 -1
+pub fn sys_exec(path: *const u8) -> isize {
+    let token = current_user_token();
+    let Some(path) = translated_str(token, path) else {
+        return -1;
+    };
+    if let Some(data) = get_app_data_by_name(path.as_str()) {
+        let task = current_task().unwrap();
+        task.exec(data);
+        0
+    } else {
+        -1
+    }
+}
+
+/// waitpid 的 System Call 实现：回收一个已经退出（僵尸状态）的子进程
+/// 参数:
+/// - pid: 要等待的子进程 PID，-1 表示等待任意一个子进程
+/// - exit_code_ptr: 用户态地址，用于写回子进程的退出码
+/// 返回值:
+/// - 回收成功时返回被回收的子进程 PID
+/// - `pid` 不是调用者任何一个（存活或僵尸）子进程的 PID 时返回 -1
+/// - `pid` 对应的子进程存在但还没有退出时返回 -2，调用方应当稍后重试
+/// - `exit_code_ptr` 没有落在调用者可写的用户页面内时返回 -1，且不会回收子进程
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if !inner
+        .children
+        .iter()
+        .any(|p| pid == -1 || pid as usize == p.pid())
+    {
+        return -1;
+    }
+    let pair = inner.children.iter().enumerate().find(|(_, p)| {
+        let p_inner = p.inner_exclusive_access();
+        p_inner.is_zombie() && (pid == -1 || pid as usize == p.pid())
+    });
+    if let Some((idx, _)) = pair {
+        let exit_code = inner.children[idx].inner_exclusive_access().exit_code;
+        if !write_translated_i32(&mut inner.memory_set, exit_code_ptr, exit_code) {
+            return -1;
+        }
+        let child = inner.children.remove(idx);
+        assert_eq!(Arc::strong_count(&child), 1);
+        child.pid() as isize
+    } else {
+        -2
+    }
+}
+
+/// 把一个 `i32` 写入用户态地址空间，借助 `translated_byte_buffer` 翻译出对应的物理字节切片
+/// 之前会先校验 `ptr` 是否落在调用者可写的用户页面内
+/// 返回值:
+/// - `true`：校验通过并写入成功
+/// - `false`：`ptr` 没有落在可写的用户页面内，调用方应当放弃这次写入
+fn write_translated_i32(memory_set: &mut crate::mm::MemorySet, ptr: *mut i32, value: i32) -> bool {
+    let size = core::mem::size_of::<i32>();
+    let Some(end) = (ptr as usize).checked_add(size) else {
+        return false;
+    };
+    if !memory_set.validate_user_range(
+        crate::mm::VirtAddr::from(ptr as usize),
+        crate::mm::VirtAddr::from(end),
+        true,
+    ) {
+        return false;
+    }
+    let buffers = translated_byte_buffer(memory_set.token(), ptr as *const u8, size);
+    let bytes = value.to_ne_bytes();
+    let mut offset = 0;
+    for buffer in buffers {
+        let len = buffer.len();
+        buffer.copy_from_slice(&bytes[offset..offset + len]);
+        offset += len;
+    }
+    true
+}
+
+/// mmap 的 System Call 实现：在当前任务的地址空间中动态插入一段新的 `Framed` 逻辑段
+/// 参数:
+/// - `start`: 映射区域的起始虚拟地址，必须按页对齐
+/// - `len`: 映射区域的长度（字节），内部按页上取整
+/// - `prot`: 低 3 位依次对应 R/W/X 权限，`U` 权限总是被自动加上
+/// 返回值:
+/// - 成功时返回 0
+/// - `start` 未按页对齐、`prot` 不合法（为 0 或设置了低 3 位之外的位）、
+///   或者请求的区间与已有逻辑段重叠时返回 -1
+pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
+    if start % PAGE_SIZE != 0 {
+        return -1;
+    }
+    if prot & !0x7 != 0 || prot & 0x7 == 0 {
+        return -1;
+    }
+    let mut permission = MapPermission::U;
+    if prot & 0b001 != 0 {
+        permission |= MapPermission::R;
+    }
+    if prot & 0b010 != 0 {
+        permission |= MapPermission::W;
+    }
+    if prot & 0b100 != 0 {
+        permission |= MapPermission::X;
+    }
+    current_mmap(start, len, permission)
+}
+
+/// munmap 的 System Call 实现：精确撤销一段之前通过 `mmap` 建立的逻辑段
+/// 参数:
+/// - `start`: 映射区域的起始虚拟地址，必须与当初 `mmap` 时完全一致
+/// - `len`: 映射区域的长度（字节）
+/// 返回值:
+/// - 成功时返回 0
+/// - 找不到起止虚拟页号与 `[start, start+len)` 精确匹配的逻辑段时返回 -1
+pub fn sys_munmap(start: usize, len: usize) -> isize {
+    current_munmap(start, len)
+}
+
+/// sbrk 的 System Call 实现：按 `increment`（可正可负，单位为字节）调整当前任务的
+/// 程序间断点，具体的地址空间改动委托给 `TaskControlBlock::change_program_brk`
+/// 参数:
+/// - `increment`: 程序间断点的调整量，正数表示扩张堆空间，负数表示收缩
+/// 返回值:
+/// - 成功时返回调整前的程序间断点（旧的 brk）
+/// - 收缩幅度超过堆区域本身时返回 -1
+pub fn sys_sbrk(increment: isize) -> isize {
+    if let Some(old_brk) = change_program_brk(increment as i32) {
+        old_brk as isize
+    } else {
+        -1
+    }
+}
+
+/// set_priority 的 System Call 实现：调整当前任务的 stride 调度优先级
+/// 参数:
+/// - `priority`: 新的优先级，必须 >= 2
+/// 返回值:
+/// - 成功时返回 `priority` 本身
+/// - `priority < 2` 时返回 -1，不做任何修改
+pub fn sys_set_priority(priority: isize) -> isize {
+    if priority < 2 || !current_set_priority(priority as usize) {
+        -1
+    } else {
+        priority
+    }
+}
+
+/// get_time 的 System Call 实现
+/// 返回值:
+/// - 自开机以来经过的毫秒数
+pub fn sys_get_time() -> isize {
+    get_time_ms() as isize
+}
+
+/// sleep 的 System Call 实现：登记当前任务的唤醒时刻，随后不断 `sys_yield`，
+/// 在此期间调度器会跳过这个任务直到 `wake_time` 过去
+/// 参数:
+/// - `ms`: 本次调用之后至少需要睡眠的毫秒数
+/// 返回值:
+/// - 恒为 0
+pub fn sys_sleep(ms: usize) -> isize {
+    let wake_time = get_time_ms() + ms;
+    current_sleep_until(wake_time);
+    while get_time_ms() < wake_time {
+        suspend_current_and_run_next();
+    }
+    0
+}
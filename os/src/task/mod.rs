@@ -1,219 +1,265 @@
-use crate::loader::{get_num_app, get_app_data};
+use crate::loader::get_app_data_by_name;
 use crate::sbi::shutdown;
 use crate::task::context::TaskContext;
+use crate::task::scheduler::{Scheduler, StrideScheduler};
 use crate::task::switch::__switch;
 use crate::trap::TrapContext;
-use crate::{config::MAX_APP_NUM, sync::UPSafeCell};
+use crate::sync::UPSafeCell;
 use crate::task::task::{TaskControlBlock, TaskStatus};
-use alloc::vec::Vec;
+use alloc::sync::Arc;
 use lazy_static::*;
-use log::{debug, info};
+use log::info;
 
 mod context;
+mod pid;
+mod scheduler;
 mod switch;
 mod task;
 
-/// 任务管理器，负责管理所有的任务
-/// 包括任务的创建、调度、切换等功能
-/// 使用 UPSafeCell 包装以实现内部可变性
-/// 任务管理器内部包含一个任务数量的变量和一个内部可变的任务管理器内部结构体
-pub struct TaskManager {
-    num_app: usize,
-    inner: UPSafeCell<TaskManagerInner>,
-}
-
-/// 任务管理器的内部结构体，包含所有任务的控制块和当前运行的任务 ID
-/// 使用数组存储所有任务的控制块，大小为 MAX_APP_NUM
-/// 当前运行的任务 ID 用于标识当前正在运行的任务
-pub struct TaskManagerInner {
-    tasks: Vec<TaskControlBlock>,
-    current_task: usize,
-}
-
-lazy_static!{
-    /// 全局唯一的任务管理器实例
-    /// 
-    /// 初始化时会加载所有用户应用程序，并将它们的状态设置为 Ready，同时预设好它们的上下文
-    pub static ref TASK_MANAGER: TaskManager = {
-        debug!("init task manager");
-        let num_app = get_num_app();
-        debug!("num_app = {}", num_app);
-        let mut tasks: Vec<TaskControlBlock> = Vec::with_capacity(MAX_APP_NUM);
-         for i in 0..num_app {
-            tasks.push(TaskControlBlock::new(
-                get_app_data(i),
-                i,
-            ));
-        }
+pub use task::TaskControlBlock;
 
-        TaskManager {
-            num_app,
-            inner: unsafe { UPSafeCell::new(TaskManagerInner {
-                tasks,
-                current_task: 0,
-            }) },
-        }
-    };
+/// 当前采用的调度策略实现，替换这一行即可切换调度算法而不影响 [`TaskManager`]
+/// 的其余代码，与 `frame_allocator.rs` 里 `FrameAllocatorImpl` 的做法一致
+type SchedulerImpl = StrideScheduler;
+
+/// 任务管理器：只负责维护一个就绪集合（具体的选取策略委托给 [`Scheduler`]），
+/// 不再关心"正在运行的是谁"，那部分职责被拆分到了 [`Processor`]
+pub struct TaskManager {
+    scheduler: SchedulerImpl,
 }
 
 impl TaskManager {
-    /// 运行第一个任务
-    /// 
-    /// 加载、运行第一个任务的过程为：
-    /// 1. 获取任务管理器的内部可变引用
-    /// 2. 将第一个任务的状态设置为 Running
-    /// 3. 获取第一个任务的上下文指针
-    /// 4. 释放任务管理器的内部可变引用
-    /// 5. 创建一个未使用的上下文指针 `_unused_dummy_ctx_ptr`，并初始化为全零
-    /// 6. 使用 `__switch` 函数切换到第一个任务的上下文
-    /// 
-    /// 返回值：
-    /// - 该函数不会返回，因为它会切换到第一个任务的上下文，之后转入用户态执行
-    fn run_first_task(&self) -> ! {
-        let mut inner = self.inner.exclusive_access();
-        let task0 = &mut inner.tasks[0];
-
-        task0.task_status = TaskStatus::Running;
-        let next_task_cx_ptr = &task0.task_cx as *const TaskContext;
-        drop(inner);
-
-        let mut _unused_dummy_ctx_ptr = TaskContext::zero_init();
-        unsafe {
-            __switch(&mut _unused_dummy_ctx_ptr as *mut TaskContext, next_task_cx_ptr);
+    pub fn new() -> Self {
+        Self {
+            scheduler: SchedulerImpl::new(),
         }
+    }
+
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        self.scheduler.add_task(task);
+    }
+
+    pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.scheduler.next_task()
+    }
+}
+
+lazy_static! {
+    /// 全局唯一的任务管理器实例，只持有处于 Ready 状态、等待被调度的任务
+    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
+        unsafe { UPSafeCell::new(TaskManager::new()) };
 
-        unreachable!()
+    /// 1 号初始进程：系统启动时加载的名为 `initproc` 的应用程序，
+    /// 在普通进程退出时没有存活的父进程可以收养孤儿任务，都会被过继给它
+    pub static ref INITPROC: Arc<TaskControlBlock> = Arc::new(TaskControlBlock::new(
+        get_app_data_by_name("initproc").unwrap(),
+    ));
+}
+
+/// 把一个任务放入就绪队列的接口函数
+pub fn add_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().add(task);
+}
+
+/// 从就绪队列中取出下一个待运行任务的接口函数
+fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().fetch()
+}
+
+/// 把初始进程放入就绪队列，内核启动时调用一次
+pub fn add_initproc() {
+    add_task(INITPROC.clone());
+}
+
+/// 处理器：记录当前这个核上正在运行的任务，以及一个专属于调度循环本身的
+/// `idle` 任务上下文，用于在没有任务可运行、或者任务让出/退出时切回调度循环
+pub struct Processor {
+    current: Option<Arc<TaskControlBlock>>,
+    idle_task_cx: TaskContext,
+}
+
+impl Processor {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init(),
+        }
     }
 
-    /// 将当前任务标记为挂起状态，通过修改任务管理器内部的任务的 `task_status` 字段实现
-    fn mark_current_suspended(&self) {
-        let mut inner = self.inner.exclusive_access();
-        let current_task = inner.current_task;
-        inner.tasks[current_task].task_status = TaskStatus::Ready;
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut TaskContext
     }
 
-    /// 将当前任务标记为退出状态，通过修改任务管理器内部的任务的 `task_status` 字段实现
-    fn mark_current_exited(&self) {
-        let mut inner = self.inner.exclusive_access();
-        let current_task = inner.current_task;
-        inner.tasks[current_task].task_status = TaskStatus::Exited;
+    pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.current.take()
     }
 
-    /// 查找下一个可运行的任务
-    /// 
-    /// 在当前任务让出 CPU 后或者当前任务退出后，调用该函数查找下一个可运行的任务
-    /// 查找过程为：
-    /// 1. 获取任务管理器的内部可变引用
-    /// 2. 从当前任务的下一个任务开始，循环查找状态为 Ready 的任务
-    /// 3. 如果找到，则返回该任务的 ID
-    /// 4. 如果没有找到，则返回 None
-    /// 
-    /// 返回值：
-    /// - 如果找到下一个可运行的任务，返回 Some(任务 ID)
-    /// - 如果没有找到可运行的任务，返回 None
-    fn find_next_task(&self) -> Option<usize> {
-        let inner = self.inner.exclusive_access();
-        let current_task = inner.current_task;
-
-        (current_task + 1..current_task + self.num_app + 1)
-            .map(|id| id % self.num_app)
-            .find(|id| inner.tasks[*id].task_status == TaskStatus::Ready)
+    pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
+        self.current.as_ref().map(Arc::clone)
     }
+}
+
+lazy_static! {
+    /// 全局唯一的处理器实例（单核）
+    pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+}
 
-    /// 切换到下一个可运行的任务
-    /// 
-    /// 切换过程为：
-    /// 1. 调用 `find_next_task` 查找下一个可运行的任务
-    /// 2. 如果找到，则将当前任务的状态设置为 Ready，将下一个任务的状态设置为 Running
-    /// 3. 获取当前任务和下一个任务的上下文指针
-    /// 4. 释放任务管理器的内部可变引用
-    /// 5. 使用 `__switch` 函数切换到下一个任务的上下文
-    /// 6. 如果没有找到可运行的任务，则打印提示信息，并调用 `shutdown` 关闭系统
-    /// 
-    /// 注意：
-    /// - 该函数假设至少有一个任务处于 Ready 状态，否则会调用 `shutdown` 关闭系统
-    fn run_next_task(&self) {
-        if let Some(next_task) = self.find_next_task() {
-            let mut inner = self.inner.exclusive_access();
-            let current_task = inner.current_task;
-            inner.tasks[next_task].task_status = TaskStatus::Running;
-            inner.current_task = next_task;
-
-            let current_task_cx_ptr = &mut inner.tasks[current_task].task_cx as *mut TaskContext;
-            let next_task_cx_ptr = &inner.tasks[next_task].task_cx as *const TaskContext;
-            drop(inner);
-
-            // 调用 __switch 对于应用的上下文进行切换
+/// 调度循环：不断从就绪队列中取出任务运行，任务让出/退出后又会通过
+/// `schedule` 切回这里，继续取下一个任务
+///
+/// 注意：
+/// - 该函数不会返回
+/// - 当就绪队列为空时说明所有任务都已经退出或陷入等待，直接关机
+pub fn run_tasks() -> ! {
+    loop {
+        let mut processor = PROCESSOR.exclusive_access();
+        if let Some(task) = fetch_task() {
+            let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+            task.activate();
+            let mut task_inner = task.inner_exclusive_access();
+            let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
+            task_inner.task_status = TaskStatus::Running;
+            drop(task_inner);
+            processor.current = Some(task);
+            drop(processor);
             unsafe {
-                __switch(current_task_cx_ptr, next_task_cx_ptr);
+                __switch(idle_task_cx_ptr, next_task_cx_ptr);
             }
         } else {
-            info!("[kernel] All tasks are completed!");
+            info!("[kernel] No task available, shutting down");
             shutdown(false);
         }
     }
+}
 
-    fn get_current_token(&self) -> usize {
-        let inner = self.inner.exclusive_access();
-        let current_task = inner.current_task;
-        inner.tasks[current_task].get_user_token()
-    }
+/// 取出当前正在运行的任务（不再放回就绪队列）的接口函数
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().take_current()
+}
 
-    fn get_current_trap_cx(&self) -> &mut TrapContext {
-        let inner = self.inner.exclusive_access();
-        let current_task = inner.current_task;
-        inner.tasks[current_task].get_trap_cx()
-    }
+/// 获取当前正在运行的任务的一份新引用的接口函数
+pub fn current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().current()
+}
 
-        /// Change the current 'Running' task's program break
-    pub fn change_current_program_brk(&self, size: i32) -> Option<usize> {
-        let mut inner = self.inner.exclusive_access();
-        let cur = inner.current_task;
-        inner.tasks[cur].change_program_brk(size)
+pub fn current_user_token() -> usize {
+    current_task().unwrap().get_user_token()
+}
+
+pub fn current_trap_cx() -> &'static mut TrapContext {
+    current_task().unwrap().inner_exclusive_access().get_trap_cx()
+}
+
+/// 把调用者的任务上下文保存起来，切回调度循环的 `idle` 上下文，
+/// 由 [`run_tasks`] 继续去取下一个就绪任务
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let mut processor = PROCESSOR.exclusive_access();
+    let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+    drop(processor);
+    unsafe {
+        __switch(switched_task_cx_ptr, idle_task_cx_ptr);
     }
 }
 
-/// 运行第一个任务的接口函数
-pub fn run_first_task() {
-    TASK_MANAGER.run_first_task();
+/// 将当前正在运行的任务挂起并切换到下一个任务的接口函数：
+/// 取出当前任务、标记为 Ready、放回就绪队列，再调度到下一个任务
+pub fn suspend_current_and_run_next() {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.task_status = TaskStatus::Ready;
+    drop(task_inner);
+
+    add_task(task);
+    schedule(task_cx_ptr);
 }
 
-/// 切换到下一个可运行任务的接口函数
-pub fn run_next_task() {
-    TASK_MANAGER.run_next_task();
+/// 将当前正在运行的任务标记为僵尸状态并切换到下一个任务的接口函数：
+/// - 记录下退出码 `exit_code`
+/// - 把它所有存活的孩子过继给 [`INITPROC`]
+/// - 自己不再放回就绪队列，等待父进程通过 `waitpid` 回收
+pub fn exit_current_and_run_next(exit_code: i32) {
+    let task = take_current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.task_status = TaskStatus::Zombie;
+    inner.exit_code = exit_code;
+
+    {
+        let mut initproc_inner = INITPROC.inner_exclusive_access();
+        for child in inner.children.iter() {
+            child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+            initproc_inner.children.push(Arc::clone(child));
+        }
+    }
+    inner.children.clear();
+    drop(inner);
+    drop(task);
+
+    let mut _unused = TaskContext::zero_init();
+    schedule(&mut _unused as *mut TaskContext);
 }
 
-/// 将当前任务标记为挂起状态的接口函数
-pub fn mark_current_suspended() {
-    TASK_MANAGER.mark_current_suspended();
+/// Change the current 'Running' task's program break
+pub fn change_program_brk(size: i32) -> Option<usize> {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().change_program_brk(size);
+    result
 }
 
-/// 将当前任务标记为退出状态的接口函数
-pub fn mark_current_exited() {
-    TASK_MANAGER.mark_current_exited();
+/// 为当前任务处理一次缺页异常的接口函数，由 trap handler 在
+/// `StorePageFault`/`LoadPageFault`/`InstructionPageFault` 发生时调用
+pub fn handle_current_page_fault(vpn: crate::mm::VirtPageNum, is_write: bool) -> bool {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().handle_page_fault(vpn, is_write);
+    result
 }
 
-/// 将当前任务挂起并切换到下一个任务的接口函数
-pub fn suspend_current_and_run_next() {
-    mark_current_suspended();
-    run_next_task();
+/// 为当前任务的地址空间动态插入一段新逻辑段的接口函数，供 `sys_mmap` 调用
+pub fn current_mmap(start: usize, len: usize, permission: crate::mm::MapPermission) -> isize {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().mmap(start, len, permission);
+    result
 }
 
-/// 将当前任务退出并切换到下一个任务的接口函数
-pub fn exit_current_and_run_next() {
-    mark_current_exited();
-    run_next_task();
+/// 撤销当前任务地址空间中一段逻辑段的接口函数，供 `sys_munmap` 调用
+pub fn current_munmap(start: usize, len: usize) -> isize {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().munmap(start, len);
+    result
 }
 
-pub fn current_user_token() -> usize {
-    TASK_MANAGER.get_current_token()
+/// 设置当前任务调度优先级的接口函数，供 `sys_set_priority` 调用
+pub fn current_set_priority(priority: usize) -> bool {
+    let task = current_task().unwrap();
+    let result = task.inner_exclusive_access().set_priority(priority);
+    result
 }
 
-pub fn current_trap_cx() -> &'static mut TrapContext {
-    TASK_MANAGER.get_current_trap_cx()
+/// 登记当前任务的唤醒时刻的接口函数，供 `sys_sleep` 调用：在 `wake_time`
+/// 之前，调度器不会再选中这个任务运行
+pub fn current_sleep_until(wake_time: usize) {
+    let task = current_task().unwrap();
+    task.inner_exclusive_access().sleep_until(wake_time);
 }
 
-/// Change the current 'Running' task's program break
-pub fn change_program_brk(size: i32) -> Option<usize> {
-    TASK_MANAGER.change_current_program_brk(size)
+/// 校验 `[ptr, ptr+len)` 是否整段落在当前任务地址空间里它有权访问的逻辑段内
+/// 的接口函数，供系统调用在解引用任何用户态指针之前调用
+/// 参数:
+/// - `ptr`/`len`: 待校验的用户态地址区间
+/// - `need_write`: 调用方是否需要写这段内存（影响检查 `R` 还是 `W` 权限）
+/// 返回值:
+/// - `true`：这段地址合法，可以安全地交给 `translated_byte_buffer` 等函数解引用
+/// - `false`：`ptr + len` 发生回绕溢出，或者这段地址有一部分没有落在任何
+///   逻辑段内，或者权限不足
+pub fn current_validate_user_range(ptr: usize, len: usize, need_write: bool) -> bool {
+    let Some(end) = ptr.checked_add(len) else {
+        return false;
+    };
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    inner.memory_set.validate_user_range(
+        crate::mm::VirtAddr::from(ptr),
+        crate::mm::VirtAddr::from(end),
+        need_write,
+    )
 }
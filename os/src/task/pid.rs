@@ -0,0 +1,99 @@
+//! 进程标识符（PID）分配与每进程专属内核栈管理
+
+use crate::config::kernel_stack_position;
+use crate::mm::{MapPermission, VirtAddr, KERNEL_SPACE};
+use crate::sync::UPSafeCell;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+
+/// 栈式 PID 分配器：优先复用被回收的 PID，与 [`crate::mm::frame_allocator::StackFrameAllocator`]
+/// 中 `recycled` 字段的思路一致——尚未分配过的 PID 才需要递增 `current`
+struct PidAllocator {
+    current: usize,
+    recycled: Vec<usize>,
+}
+
+impl PidAllocator {
+    pub fn new() -> Self {
+        Self {
+            current: 0,
+            recycled: Vec::new(),
+        }
+    }
+
+    pub fn alloc(&mut self) -> PidHandle {
+        if let Some(pid) = self.recycled.pop() {
+            PidHandle(pid)
+        } else {
+            self.current += 1;
+            PidHandle(self.current - 1)
+        }
+    }
+
+    pub fn dealloc(&mut self, pid: usize) {
+        assert!(pid < self.current);
+        assert!(
+            !self.recycled.iter().any(|ppid| *ppid == pid),
+            "pid {} has been deallocated!",
+            pid
+        );
+        self.recycled.push(pid);
+    }
+}
+
+lazy_static! {
+    static ref PID_ALLOCATOR: UPSafeCell<PidAllocator> =
+        unsafe { UPSafeCell::new(PidAllocator::new()) };
+}
+
+/// 一个 PID 的 RAII 句柄：持有期间这个 PID 被视为已分配，`Drop` 时自动归还
+/// 给 [`PID_ALLOCATOR`]
+pub struct PidHandle(pub usize);
+
+impl Drop for PidHandle {
+    fn drop(&mut self) {
+        PID_ALLOCATOR.exclusive_access().dealloc(self.0);
+    }
+}
+
+/// 从全局 PID 分配器中分配一个新的 PID
+pub fn pid_alloc() -> PidHandle {
+    PID_ALLOCATOR.exclusive_access().alloc()
+}
+
+/// 一个任务专属的内核栈，以 `Framed` 逻辑段的形式插入 [`KERNEL_SPACE`]，
+/// 位置由 [`kernel_stack_position`] 根据 PID 计算得到，两两之间留有一个
+/// 未映射的 guard page 以捕获栈溢出
+pub struct KernelStack {
+    pid: usize,
+}
+
+impl KernelStack {
+    /// 为 `pid_handle` 对应的 PID 在内核地址空间中开辟一段新的内核栈
+    pub fn new(pid_handle: &PidHandle) -> Self {
+        let pid = pid_handle.0;
+        let (kernel_stack_bottom, kernel_stack_top) = kernel_stack_position(pid);
+        KERNEL_SPACE.exclusive_access().insert_framed_area(
+            VirtAddr::from(kernel_stack_bottom),
+            VirtAddr::from(kernel_stack_top),
+            MapPermission::R | MapPermission::W,
+        );
+        Self { pid }
+    }
+
+    /// 返回这个内核栈的栈顶地址，供 `TaskContext::goto_trap_return` 初始化 `sp` 使用
+    pub fn get_top(&self) -> usize {
+        let (_, kernel_stack_top) = kernel_stack_position(self.pid);
+        kernel_stack_top
+    }
+}
+
+impl Drop for KernelStack {
+    fn drop(&mut self) {
+        let (kernel_stack_bottom, _) = kernel_stack_position(self.pid);
+        let kernel_stack_bottom_vpn = VirtAddr::from(kernel_stack_bottom).floor();
+        KERNEL_SPACE
+            .exclusive_access()
+            .remove_area_with_start_vpn(kernel_stack_bottom_vpn);
+    }
+}
@@ -0,0 +1,91 @@
+//! 可插拔的调度策略：`Scheduler` trait 定义了任务管理器需要的最小接口，
+//! [`StrideScheduler`] 是当前采用的 stride 调度算法实现
+
+use crate::task::task::TaskControlBlock;
+use crate::timer::get_time_ms;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// 调度策略的统一接口，`TaskManager` 只通过这个 trait 与具体算法打交道，
+/// 从而可以在不改动 `TaskManager` 的情况下替换调度算法
+pub trait Scheduler {
+    /// 把一个任务加入就绪集合
+    fn add_task(&mut self, task: Arc<TaskControlBlock>);
+    /// 查看下一个会被调度的任务，但不把它从就绪集合中取出、也不更新它的调度状态
+    fn peek_next_task(&self) -> Option<Arc<TaskControlBlock>>;
+    /// 取出下一个应当被调度的任务，并在返回前完成这个任务自身的调度记账
+    /// （对 stride 调度来说，就是把它的 `pass` 加到 `stride` 上）
+    fn next_task(&mut self) -> Option<Arc<TaskControlBlock>>;
+}
+
+/// 按照 stride 算法比较两个 `stride` 的先后关系：由于 `stride` 是一个会无限增长、
+/// 不断回绕的定长整数，直接比较大小在回绕之后会出错，这里用 `wrapping_sub` 之后
+/// 转换成有符号数的技巧来判断——只要相邻两次调度之间 `pass <= BIG_STRIDE`，
+/// 任意两个任务的 `stride` 之差就不会超过 `BIG_STRIDE`，远小于回绕半径，
+/// 符号位就能正确反映谁更小
+fn stride_less(a: usize, b: usize) -> bool {
+    (a.wrapping_sub(b) as isize) < 0
+}
+
+/// 基于 stride 算法的调度器：每次调度选出就绪集合中 `stride` 最小的任务运行，
+/// `priority` 越大，`pass = BIG_STRIDE / priority` 越小，`stride` 增长得越慢，
+/// 因而被调度的频率越高，从而实现任务之间按优先级成比例地分享 CPU
+pub struct StrideScheduler {
+    ready: Vec<Arc<TaskControlBlock>>,
+}
+
+impl StrideScheduler {
+    pub fn new() -> Self {
+        Self { ready: Vec::new() }
+    }
+
+    /// 在若干个候选下标中找到 `stride` 最小的一个
+    fn min_stride_among(&self, candidates: impl Iterator<Item = usize>) -> Option<usize> {
+        candidates.min_by(|&a, &b| {
+            let stride_a = self.ready[a].inner_exclusive_access().stride;
+            let stride_b = self.ready[b].inner_exclusive_access().stride;
+            if stride_less(stride_a, stride_b) {
+                Ordering::Less
+            } else if stride_less(stride_b, stride_a) {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+    }
+
+    /// 在就绪集合中找到 `stride` 最小的任务的下标，跳过 `wake_time` 仍晚于
+    /// 当前时刻的睡眠中任务；如果所有任务都还在睡眠，则退而求其次选出
+    /// `stride` 最小的那一个，避免 `next_task` 无谓地返回 `None`
+    fn min_stride_index(&self) -> Option<usize> {
+        let now = get_time_ms();
+        let awake = (0..self.ready.len()).filter(|&idx| {
+            self.ready[idx]
+                .inner_exclusive_access()
+                .wake_time
+                .map_or(true, |wake_time| wake_time <= now)
+        });
+        self.min_stride_among(awake)
+            .or_else(|| self.min_stride_among(0..self.ready.len()))
+    }
+}
+
+impl Scheduler for StrideScheduler {
+    fn add_task(&mut self, task: Arc<TaskControlBlock>) {
+        self.ready.push(task);
+    }
+
+    fn peek_next_task(&self) -> Option<Arc<TaskControlBlock>> {
+        self.min_stride_index().map(|idx| Arc::clone(&self.ready[idx]))
+    }
+
+    fn next_task(&mut self) -> Option<Arc<TaskControlBlock>> {
+        let idx = self.min_stride_index()?;
+        let task = self.ready.remove(idx);
+        let mut inner = task.inner_exclusive_access();
+        inner.stride = inner.stride.wrapping_add(inner.pass);
+        drop(inner);
+        Some(task)
+    }
+}
@@ -0,0 +1,240 @@
+use crate::config::{BIG_STRIDE, DEFAULT_PRIORITY, TRAP_CONTEXT};
+use crate::mm::{MapPermission, MemorySet, VirtAddr};
+use crate::sync::UPSafeCell;
+use crate::task::context::TaskContext;
+use crate::task::pid::{pid_alloc, KernelStack, PidHandle};
+use crate::trap::TrapContext;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::cell::RefMut;
+
+/// 任务的运行状态
+#[derive(Copy, Clone, PartialEq)]
+pub enum TaskStatus {
+    Ready,
+    Running,
+    /// 任务已经退出但还未被父进程通过 `waitpid` 回收，保留着它的 `exit_code`
+    Zombie,
+}
+
+/// 任务控制块，保存一个进程在内核中的全部状态：它的 PID、专属内核栈，以及
+/// 一组可变的运行期状态（运行状态、任务上下文、地址空间、父子关系、退出码）
+///
+/// 可变部分包装在 `UPSafeCell` 中，是因为 `TaskControlBlock` 本身以
+/// `Arc<TaskControlBlock>` 的形式被 `TaskManager`、`Processor`、父进程的
+/// `children` 列表等多处共享持有，只能通过内部可变性来修改
+pub struct TaskControlBlock {
+    pub pid: PidHandle,
+    pub kernel_stack: KernelStack,
+    inner: UPSafeCell<TaskControlBlockInner>,
+}
+
+pub struct TaskControlBlockInner {
+    pub task_status: TaskStatus,
+    pub task_cx: TaskContext,
+    pub memory_set: MemorySet,
+    pub parent: Option<Weak<TaskControlBlock>>,
+    pub children: Vec<Arc<TaskControlBlock>>,
+    pub exit_code: i32,
+    /// 堆区域的起始地址，与 `from_elf` 中预留的零长度 `Framed` 逻辑段的起始地址一致，
+    /// `program_brk` 永远不会被 `sbrk` 收缩到比它更小的地方
+    pub heap_bottom: usize,
+    /// 当前的程序间断点（program break），即堆区域的结束地址
+    pub program_brk: usize,
+    /// 调度优先级，必须 >= 2，由 `sys_set_priority` 调整；值越大，`pass` 越小，
+    /// 被 [`crate::task::scheduler::StrideScheduler`] 调度的频率越高
+    pub priority: usize,
+    /// 每次被调度时累加到 `stride` 上的步长，等于 `BIG_STRIDE / priority`
+    pub pass: usize,
+    /// stride 调度算法的累加器，调度器每次选出其中最小的任务运行
+    pub stride: usize,
+    /// 这个任务通过 `sys_sleep` 登记的唤醒时刻（`get_time_ms()` 的绝对值）；
+    /// `None` 表示没有在睡眠。调度器在选取下一个任务时会跳过
+    /// `wake_time` 仍晚于当前时刻的任务，避免它被无谓地唤醒又立刻重新入睡
+    pub wake_time: Option<usize>,
+}
+
+impl TaskControlBlock {
+    /// 独占访问这个任务的可变内部状态
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+
+    /// 根据 ELF 数据创建一个全新的任务控制块（没有父进程），分配新的 PID 和
+    /// 专属内核栈，并把任务上下文的 `ra` 指向 `trap_return`
+    pub fn new(elf_data: &'static [u8]) -> Self {
+        let (memory_set, user_stack_top, _entry_point) = MemorySet::from_elf(elf_data);
+        let pid_handle = pid_alloc();
+        let kernel_stack = KernelStack::new(&pid_handle);
+        let kernel_stack_top = kernel_stack.get_top();
+        Self {
+            pid: pid_handle,
+            kernel_stack,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    task_status: TaskStatus::Ready,
+                    task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    memory_set,
+                    parent: None,
+                    children: Vec::new(),
+                    exit_code: 0,
+                    heap_bottom: user_stack_top,
+                    program_brk: user_stack_top,
+                    priority: DEFAULT_PRIORITY,
+                    pass: BIG_STRIDE / DEFAULT_PRIORITY,
+                    stride: 0,
+                    wake_time: None,
+                })
+            },
+        }
+    }
+
+    /// 以写时复制的方式从 `self` 派生出一个子任务，供 `fork` 使用：子任务
+    /// 拥有自己的 PID、内核栈和 `TaskContext`，但与父任务共享（COW）同一份
+    /// 用户态数据，并被登记为父任务的孩子
+    pub fn fork(self: &Arc<TaskControlBlock>) -> Arc<TaskControlBlock> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let memory_set = parent_inner.memory_set.clone_cow();
+        let pid_handle = pid_alloc();
+        let kernel_stack = KernelStack::new(&pid_handle);
+        let kernel_stack_top = kernel_stack.get_top();
+        let task_control_block = Arc::new(TaskControlBlock {
+            pid: pid_handle,
+            kernel_stack,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    task_status: TaskStatus::Ready,
+                    task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    memory_set,
+                    parent: Some(Arc::downgrade(self)),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    heap_bottom: parent_inner.heap_bottom,
+                    program_brk: parent_inner.program_brk,
+                    priority: parent_inner.priority,
+                    pass: parent_inner.pass,
+                    stride: 0,
+                    wake_time: None,
+                })
+            },
+        });
+        parent_inner.children.push(Arc::clone(&task_control_block));
+        // 子进程的陷入上下文与父进程完全一致，唯一的区别是 `fork` 的返回值：
+        // 子进程看到的返回值固定为 0（约定在 a0 寄存器，即 x[10]）
+        let trap_cx = task_control_block.inner_exclusive_access().get_trap_cx();
+        trap_cx.x[10] = 0;
+        task_control_block
+    }
+
+    /// 用 `elf_data` 重新初始化这个任务的地址空间，实现 `exec`：PID、内核栈
+    /// 和父子关系都保持不变，只有地址空间、程序间断点和陷入上下文被替换为
+    /// 新加载的应用程序的
+    pub fn exec(&self, elf_data: &'static [u8]) {
+        let (memory_set, user_stack_top, entry_point) = MemorySet::from_elf(elf_data);
+        let trap_cx = TrapContext::app_init_context(entry_point, user_stack_top);
+        let mut inner = self.inner_exclusive_access();
+        inner.memory_set = memory_set;
+        inner.heap_bottom = user_stack_top;
+        inner.program_brk = user_stack_top;
+        *inner.get_trap_cx() = trap_cx;
+    }
+
+    /// 返回这个任务对应地址空间的 satp 取值，供 trap 返回时切换页表使用
+    pub fn get_user_token(&self) -> usize {
+        self.inner_exclusive_access().memory_set.token()
+    }
+
+    /// 把这个任务的地址空间安装进 `satp`，使接下来的取指和访存都发生在它
+    /// 自己独立的页表中
+    pub fn activate(&self) {
+        self.inner_exclusive_access().memory_set.activate();
+    }
+
+    pub fn pid(&self) -> usize {
+        self.pid.0
+    }
+}
+
+impl TaskControlBlockInner {
+    /// 返回这个任务保存在自己地址空间 `TRAP_CONTEXT` 处的陷入上下文
+    pub fn get_trap_cx(&self) -> &'static mut TrapContext {
+        let ppn = self
+            .memory_set
+            .translate(VirtAddr::from(TRAP_CONTEXT).floor())
+            .unwrap()
+            .ppn();
+        unsafe { &mut *(ppn.get_bytes_array().as_mut_ptr() as *mut TrapContext) }
+    }
+
+    pub fn is_zombie(&self) -> bool {
+        self.task_status == TaskStatus::Zombie
+    }
+
+    /// 设置这个任务的调度优先级，同步重新计算 `pass = BIG_STRIDE / priority`
+    /// 参数:
+    /// - `priority`: 新的优先级，必须 >= 2
+    /// 返回值:
+    /// - `true`：设置成功
+    /// - `false`：`priority < 2`，没有做任何修改
+    pub fn set_priority(&mut self, priority: usize) -> bool {
+        if priority < 2 {
+            return false;
+        }
+        self.priority = priority;
+        self.pass = BIG_STRIDE / priority;
+        true
+    }
+
+    /// 登记这个任务的唤醒时刻，供 `sys_sleep` 使用：在 `wake_time` 之前，
+    /// 调度器不会选中这个任务运行
+    pub fn sleep_until(&mut self, wake_time: usize) {
+        self.wake_time = Some(wake_time);
+    }
+
+    /// 按 `size`（可正可负）调整这个任务的程序间断点（program break），
+    /// 具体的地址空间改动委托给 `MemorySet::append_to`/`shrink_to`
+    ///
+    /// 返回值：
+    /// - `Some(old_brk)`：调整成功，返回调整前的程序间断点
+    /// - `None`：`size` 为负且收缩幅度超过了堆区域本身（`new_brk < heap_bottom`）
+    pub fn change_program_brk(&mut self, size: i32) -> Option<usize> {
+        let old_brk = self.program_brk;
+        let new_brk = old_brk as isize + size as isize;
+        if new_brk < self.heap_bottom as isize {
+            return None;
+        }
+        let new_brk = new_brk as usize;
+        let result = if size >= 0 {
+            self.memory_set
+                .append_to(self.heap_bottom.into(), new_brk.into())
+        } else {
+            self.memory_set
+                .shrink_to(self.heap_bottom.into(), new_brk.into())
+        };
+        if result {
+            self.program_brk = new_brk;
+            Some(old_brk)
+        } else {
+            None
+        }
+    }
+
+    /// 处理这个任务地址空间中发生的一次缺页异常，返回是否成功建立/修复了映射
+    pub fn handle_page_fault(&mut self, vpn: crate::mm::VirtPageNum, is_write: bool) -> bool {
+        self.memory_set.handle_page_fault(vpn, is_write)
+    }
+
+    /// 在这个任务的地址空间中 `mmap` 一段新的逻辑段，委托给 `MemorySet::mmap`
+    pub fn mmap(&mut self, start: usize, len: usize, permission: MapPermission) -> isize {
+        let start_va = VirtAddr::from(start);
+        let end_va = VirtAddr::from(start + len);
+        self.memory_set.mmap(start_va, end_va, permission)
+    }
+
+    /// 在这个任务的地址空间中 `munmap` 一段之前 `mmap` 过的逻辑段，委托给 `MemorySet::munmap`
+    pub fn munmap(&mut self, start: usize, len: usize) -> isize {
+        let start_va = VirtAddr::from(start);
+        let end_va = VirtAddr::from(start + len);
+        self.memory_set.munmap(start_va, end_va)
+    }
+}
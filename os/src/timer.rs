@@ -1,8 +1,6 @@
 use riscv::register::time;
-use sbi_rt::set_timer;
 
-use crate::config::CLOCK_FREQ;
-const TICKS_PER_SEC: usize = 100;
+use crate::config::{CLOCK_FREQ, TICKS_PER_SEC};
 const MSEC_PER_SEC: usize = 1_000_000;
 
 pub fn get_time() -> usize {
@@ -13,6 +11,15 @@ pub fn get_time_ms() -> usize {
     get_time() / (CLOCK_FREQ / MSEC_PER_SEC)
 }
 
+/// 通过 SBI 调用设置下一次时钟中断的触发时刻
+/// 参数:
+/// - `next`: 下一次时钟中断触发时 `time` 寄存器应达到的值
+pub fn set_timer(next: usize) {
+    sbi_rt::set_timer(next as u64);
+}
+
+/// 设置下一次时钟中断，触发时刻为当前时刻往后 `CLOCK_FREQ / TICKS_PER_SEC`，
+/// 即按照 `TICKS_PER_SEC` 规定的频率触发时钟中断
 pub fn set_next_trigger() {
-    set_timer((get_time() + CLOCK_FREQ / TICKS_PER_SEC) as u64);
+    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
 }
\ No newline at end of file
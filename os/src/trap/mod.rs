@@ -2,21 +2,35 @@ mod context;
 
 use riscv::register::{
     mtvec::TrapMode,
-    scause::{self, Exception, Trap},
-    stval, stvec,
+    scause::{self, Exception, Interrupt, Trap},
+    sie, stval, stvec,
 };
 use core::{arch::global_asm, panic};
 
-use crate::{println, syscall::syscall};
+use crate::{
+    mm::VirtAddr,
+    println,
+    syscall::syscall,
+    task::{exit_current_and_run_next, handle_current_page_fault, suspend_current_and_run_next},
+    timer::set_next_trigger,
+};
 
 // 汇编代码文件，定义了陷入处理程序的入口
 global_asm!(include_str!("trap.S"));
 
 /// 陷入机制的初始化函数
+///
+/// 关于嵌套时钟中断的安全性：RISC-V 进入陷入处理时硬件会自动清除 `sstatus.SIE`
+/// （并把之前的值保存到 `sstatus.SPIE`），所以 `trap_handler` 内部重新设置下一次
+/// 触发时刻、挂起当前任务这整个过程都运行在中断被屏蔽的状态下，不会被同级的
+/// 时钟中断再次打断而破坏正在构建中的内核栈帧；只有执行 `sret` 真正返回用户态时，
+/// `SIE` 才会恢复，下一次时钟中断才可能发生
 /// 该函数设置陷入处理程序的入口地址和模式
 /// 具体来说，它将 stvec 寄存器设置为 __alltraps 函数的地址
 /// 并将陷入模式设置为 TrapMode::Direct
 /// 这样所有的陷入（异常和中断）都会跳转到 __alltraps 进行处理
+/// 同时开启 `sie.STIE` 位以允许时钟中断，并设置第一次时钟中断的触发时刻，
+/// 从而让内核能够以时间片轮转的方式抢占应用程序
 /// 注意:
 /// - 该函数必须在内核初始化阶段调用一次
 /// - 该函数使用了 unsafe 代码块，因为直接操作硬件寄存器
@@ -24,7 +38,9 @@ pub fn init() {
     unsafe extern "C" { safe fn __alltraps(); }
     unsafe {
         stvec::write(__alltraps as usize, TrapMode::Direct);
+        sie::set_stimer();
     }
+    set_next_trigger();
 }
 
 /// 通用陷入处理函数
@@ -32,8 +48,11 @@ pub fn init() {
 /// 进行不同的处理:
 /// - 如果是用户态触发的系统调用，则调用 syscall 函数处理
 ///   并将结果存储在 x[0] 寄存器中，然后返回用户态
-/// - 如果是存储错误或存储页面错误，则打印错误信息并杀死当前应用程序
+/// - 如果是存储/取指/加载页面错误，则先尝试让当前任务在自己的地址空间中按需建立映射
+///   （服务于懒加载、写时复制和已换出的页面），处理不了时（没有逻辑段拥有这个页面，
+///   或者这次访问不被该逻辑段允许）才打印错误信息并杀死当前应用程序
 /// - 如果是非法指令异常，则打印错误信息并杀死当前应用程序
+/// - 如果是时钟中断，则重新设置下一次触发时刻，并挂起当前任务切换到下一个就绪任务
 /// - 对于其他未处理的异常，函数会 panic
 /// 参数:
 /// - cx: 当前的 TrapContext，上下文信息
@@ -54,11 +73,31 @@ pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
             cx.sepc += 4;
             cx.x[10] = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]) as usize;
         },
-        Trap::Exception(Exception::StoreFault) | Trap::Exception(Exception::StorePageFault) => {
-            println!("[kernel] Page fault in application, bad addr = {:#x}, sepc = {:#x}", stval, cx.sepc);
-            println!("[kernel] Killing application...");
-            panic!("Page fault in application");
-            // run_next_app();
+        Trap::Exception(Exception::StoreFault)
+        | Trap::Exception(Exception::StorePageFault)
+        | Trap::Exception(Exception::LoadFault)
+        | Trap::Exception(Exception::LoadPageFault)
+        | Trap::Exception(Exception::InstructionPageFault) => {
+            // 懒加载的逻辑段（demand-paged LOAD 段、零填充的栈/堆）在第一次被访问时
+            // 还没有建立页表映射，这里会触发上述几种缺页异常。先尝试在当前任务的
+            // MemorySet 中按需建立映射（或解决写时复制、换入已换出的页面）；当没有任何
+            // 逻辑段拥有这个虚拟页号，或者这次访问不被该逻辑段允许时，才说明这是一次
+            // 真正的非法访问，杀死触发异常的任务
+            let vpn = VirtAddr::from(stval).floor();
+            let is_write = matches!(
+                scause.cause(),
+                Trap::Exception(Exception::StoreFault) | Trap::Exception(Exception::StorePageFault)
+            );
+            if handle_current_page_fault(vpn, is_write) {
+                // 映射已经按需建立，直接返回用户态重新执行触发异常的指令
+            } else {
+                println!(
+                    "[kernel] Page fault in application, bad addr = {:#x}, sepc = {:#x}",
+                    stval, cx.sepc
+                );
+                println!("[kernel] Killing application...");
+                exit_current_and_run_next(-2);
+            }
         },
         Trap::Exception(Exception::IllegalInstruction) => {
             println!("[kernel] Illegal instruction in application, sepc = {:#x}", cx.sepc);
@@ -66,6 +105,12 @@ pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
             panic!("Illegal instruction in application");
             // run_next_app();
         },
+        Trap::Interrupt(Interrupt::SupervisorTimer) => {
+            // 时钟中断：重新设置下一次触发时刻，并将当前任务挂起，切换到下一个就绪任务，
+            // 从而实现不依赖应用程序主动让出 CPU 的抢占式时间片轮转调度
+            set_next_trigger();
+            suspend_current_and_run_next();
+        },
         _ => {
             panic!(
                 "Unsupported trap {:?}, stval = {:#x}, sepc = {:#x}, sstatus = {:#x}",
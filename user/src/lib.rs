@@ -36,10 +36,86 @@ fn main() -> i32 {
 
 use syscall::*;
 
+pub fn read(fd: usize, buf: &mut [u8]) -> isize {
+    sys_read(fd, buf)
+}
+
 pub fn write(fd: usize, buf: &[u8]) -> isize {
     sys_write(fd, buf)
 }
 
 pub fn exit(exit_code: i32) -> isize {
     sys_exit(exit_code);
+}
+
+pub fn yield_() -> isize {
+    sys_yield()
+}
+
+pub fn getpid() -> isize {
+    sys_getpid()
+}
+
+/// 在当前进程地址空间里插入一段新的匿名映射，`start` 必须页对齐，
+/// `prot` 的 bit0/bit1/bit2 分别表示可读/可写/可执行
+pub fn mmap(start: usize, len: usize, prot: usize) -> isize {
+    sys_mmap(start, len, prot)
+}
+
+/// 撤销一段通过 `mmap` 建立的映射，`[start, start+len)` 必须与建立时的区间完全一致
+pub fn munmap(start: usize, len: usize) -> isize {
+    sys_munmap(start, len)
+}
+
+/// 调整当前进程的堆大小（程序间断点），正数扩张、负数收缩，
+/// 返回值是调整前的程序间断点，收缩幅度超过堆大小时返回 -1
+pub fn sbrk(increment: isize) -> isize {
+    sys_sbrk(increment)
+}
+
+pub fn fork() -> isize {
+    sys_fork()
+}
+
+pub fn exec(path: &str) -> isize {
+    sys_exec(path)
+}
+
+/// 等待任意一个子进程退出并回收它，期间子进程尚未退出时反复让出 CPU
+pub fn wait(exit_code: &mut i32) -> isize {
+    loop {
+        match sys_waitpid(-1, exit_code as *mut _) {
+            -2 => {
+                yield_();
+            }
+            exit_pid => return exit_pid,
+        }
+    }
+}
+
+/// 等待 PID 为 `pid` 的子进程退出并回收它，期间它尚未退出时反复让出 CPU
+pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
+    loop {
+        match sys_waitpid(pid as isize, exit_code as *mut _) {
+            -2 => {
+                yield_();
+            }
+            exit_pid => return exit_pid,
+        }
+    }
+}
+
+/// 设置当前进程的 stride 调度优先级，`prio` 必须 >= 2
+pub fn set_priority(prio: isize) -> isize {
+    sys_set_priority(prio)
+}
+
+/// 获取自开机以来经过的毫秒数
+pub fn get_time() -> isize {
+    sys_get_time()
+}
+
+/// 让当前进程至少睡眠 `ms` 毫秒
+pub fn sleep(ms: usize) -> isize {
+    sys_sleep(ms)
 }
\ No newline at end of file
@@ -1,7 +1,19 @@
 use core::arch::asm;
 
+const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_SLEEP: usize = 101;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_SBRK: usize = 214;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_WAITPID: usize = 260;
 
 /// 通过汇编代码发起系统调用的具体函数，使用 asm 宏嵌入 ecall 指令实现
 /// asm 宏可以将汇编代码嵌入到局部的 Rust 实现的函数上下文中
@@ -27,6 +39,15 @@ fn syscall(id: usize, args :[usize; 3]) -> isize {
 /// 根据RiscV的系统调用规范定义系统调用接口，
 /// 本质是使用 Rust 对汇编的封装调用
 
+/// 从文件描述符 fd 指向的文件中读取数据到内存缓冲区中
+/// fd: 文件描述符
+/// buf: 接收数据的缓冲区
+/// 返回值: 成功读取的字节数，失败返回负数错误码
+/// syscall ID: 63
+pub fn sys_read(fd: usize, buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_READ, [fd, buf.as_mut_ptr() as usize, buf.len()])
+}
+
 /// 将内存中的数据写入到文件描述符 fd 指向的文件中
 /// fd: 文件描述符
 /// buf: 要写入的数据缓冲区
@@ -43,4 +64,93 @@ pub fn sys_write(fd: usize, buf: &[u8]) -> isize {
 pub fn sys_exit(exit_code: i32) -> ! {
     syscall(SYSCALL_EXIT, [exit_code as usize, 0, 0]);
     panic!("sys_exit never returns!");
+}
+
+/// 主动放弃 CPU 使用权，让出给其他 Ready 状态的任务运行
+/// 返回值: 目前固定返回 0
+/// syscall ID: 124
+pub fn sys_yield() -> isize {
+    syscall(SYSCALL_YIELD, [0, 0, 0])
+}
+
+/// 获取当前进程的 PID
+/// 返回值: 当前进程的 PID
+/// syscall ID: 172
+pub fn sys_getpid() -> isize {
+    syscall(SYSCALL_GETPID, [0, 0, 0])
+}
+
+/// 在当前进程地址空间里插入一段新的匿名映射
+/// start: 起始虚拟地址，必须按页对齐
+/// len: 映射长度，单位字节
+/// prot: 权限位，bit0/bit1/bit2 分别表示可读/可写/可执行，取值必须在 [1,7] 之间
+/// 返回值: 成功返回 0；`start` 未页对齐、`prot` 不合法、或者与已有映射重叠时返回 -1
+/// syscall ID: 222
+pub fn sys_mmap(start: usize, len: usize, prot: usize) -> isize {
+    syscall(SYSCALL_MMAP, [start, len, prot])
+}
+
+/// 撤销当前进程地址空间里一段已存在的映射
+/// start/len: `[start, start+len)` 必须与建立映射时的区间完全一致
+/// 返回值: 成功返回 0，找不到精确匹配的映射时返回 -1
+/// syscall ID: 215
+pub fn sys_munmap(start: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNMAP, [start, len, 0])
+}
+
+/// 调整当前进程的程序间断点，从而增长或收缩它的堆
+/// increment: 调整量，单位字节，正数扩张、负数收缩
+/// 返回值: 成功时返回调整前的程序间断点（旧的 brk）；收缩幅度超过堆区域本身时返回 -1
+/// syscall ID: 214
+pub fn sys_sbrk(increment: isize) -> isize {
+    syscall(SYSCALL_SBRK, [increment as usize, 0, 0])
+}
+
+/// 复制当前进程，创建一个新的子进程
+/// 返回值: 在父进程中返回子进程的 PID，在子进程中返回 0
+/// syscall ID: 220
+pub fn sys_fork() -> isize {
+    syscall(SYSCALL_FORK, [0, 0, 0])
+}
+
+/// 将当前进程的地址空间替换为 `path` 指向的应用程序
+/// path: 应用程序名称字符串，调用方必须自己在末尾加上 '\0'，
+/// 因为内核是按照 NUL 结尾来读取这个字符串的
+/// 返回值: 找不到同名应用程序时返回 -1，成功时不会返回到调用处
+/// syscall ID: 221
+pub fn sys_exec(path: &str) -> isize {
+    syscall(SYSCALL_EXEC, [path.as_ptr() as usize, 0, 0])
+}
+
+/// 等待一个子进程退出并回收它
+/// pid: 要等待的子进程 PID，-1 表示等待任意一个子进程
+/// exit_code: 用于写回子进程退出码的地址
+/// 返回值: 成功时返回被回收的子进程 PID；子进程尚未退出时返回 -2；
+/// `pid` 不是调用者任何一个子进程时返回 -1
+/// syscall ID: 260
+pub fn sys_waitpid(pid: isize, exit_code: *mut i32) -> isize {
+    syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, 0])
+}
+
+/// 设置当前进程的 stride 调度优先级
+/// prio: 新的优先级，必须 >= 2
+/// 返回值: 成功时返回 `prio` 本身，`prio < 2` 时返回 -1
+/// syscall ID: 140
+pub fn sys_set_priority(prio: isize) -> isize {
+    syscall(SYSCALL_SET_PRIORITY, [prio as usize, 0, 0])
+}
+
+/// 获取自开机以来经过的毫秒数
+/// 返回值: 当前时刻，单位毫秒
+/// syscall ID: 169
+pub fn sys_get_time() -> isize {
+    syscall(SYSCALL_GET_TIME, [0, 0, 0])
+}
+
+/// 让当前进程至少睡眠 `ms` 毫秒，期间反复让出 CPU 给其他 Ready 状态的任务
+/// ms: 最短睡眠时长，单位毫秒
+/// 返回值: 恒为 0
+/// syscall ID: 101
+pub fn sys_sleep(ms: usize) -> isize {
+    syscall(SYSCALL_SLEEP, [ms, 0, 0])
 }
\ No newline at end of file